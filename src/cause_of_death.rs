@@ -1,18 +1,32 @@
 use std::{fmt::Display, str::FromStr};
 
+use serde::{Serialize, Serializer};
+
 /// How many causes of death there are.
 pub const CAUSES_OF_DEATH: usize = 29;
 
 #[cfg(test)]
 use strum::EnumCount;
 
-use crate::Error;
+/// How many categories of death there are.
+pub const DEATH_CATEGORIES: usize = 6;
+
+use crate::{Error, InstanceCounter, Weapon, WeaponCounter};
 #[cfg(test)]
-// Ensure `CAUSES_OF_DEATH` is correct
+// Ensure `CAUSES_OF_DEATH` and `DEATH_CATEGORIES` are correct
 static_assertions::const_assert_eq!(
     CAUSES_OF_DEATH,
     CauseOfDeath::COUNT
 );
+#[cfg(test)]
+static_assertions::const_assert_eq!(
+    DEATH_CATEGORIES,
+    DeathCategory::COUNT
+);
+
+/// Counts how many deaths fell into each category.
+pub type DeathCategoryCounter =
+    InstanceCounter<DeathCategory, DEATH_CATEGORIES>;
 
 /// Possible causes of death within a Q3A game
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -86,6 +100,199 @@ impl CauseOfDeath {
             CauseOfDeath::Unknown => "MOD_UNKNOWN",
         }
     }
+
+    /// The weapon that caused this death, if any.
+    ///
+    /// Splash damage counts towards the weapon that caused it,
+    /// e.g. both `Rocket` and `RocketSplash` map to
+    /// `Weapon::RocketLauncher`.
+    pub fn weapon(self) -> Option<Weapon> {
+        let weapon = match self {
+            CauseOfDeath::Shotgun => Weapon::Shotgun,
+            CauseOfDeath::Gauntlet => Weapon::Gauntlet,
+            CauseOfDeath::Machinegun => Weapon::Machinegun,
+            CauseOfDeath::Grenade
+            | CauseOfDeath::GrenadeSplash => {
+                Weapon::GrenadeLauncher
+            }
+            CauseOfDeath::Rocket
+            | CauseOfDeath::RocketSplash => {
+                Weapon::RocketLauncher
+            }
+            CauseOfDeath::Plasma
+            | CauseOfDeath::PlasmaSplash => Weapon::PlasmaGun,
+            CauseOfDeath::Railgun => Weapon::Railgun,
+            CauseOfDeath::Lightning => Weapon::LightningGun,
+            CauseOfDeath::Bfg | CauseOfDeath::BfgSplash => {
+                Weapon::Bfg
+            }
+            CauseOfDeath::Grapple => Weapon::GrapplingHook,
+            CauseOfDeath::Nail => Weapon::Nailgun,
+            CauseOfDeath::ProximityMine => Weapon::ProxLauncher,
+            CauseOfDeath::Chaingun => Weapon::Chaingun,
+            CauseOfDeath::Water
+            | CauseOfDeath::Slime
+            | CauseOfDeath::Lava
+            | CauseOfDeath::Crush
+            | CauseOfDeath::Telefrag
+            | CauseOfDeath::Falling
+            | CauseOfDeath::Suicide
+            | CauseOfDeath::TargetLaser
+            | CauseOfDeath::TriggerHurt
+            | CauseOfDeath::Kamikaze
+            | CauseOfDeath::Juiced
+            | CauseOfDeath::Unknown => return None,
+        };
+
+        Some(weapon)
+    }
+
+    /// What kind of death this is.
+    pub fn category(self) -> DeathCategory {
+        match self {
+            CauseOfDeath::Shotgun
+            | CauseOfDeath::Gauntlet
+            | CauseOfDeath::Machinegun
+            | CauseOfDeath::Grenade
+            | CauseOfDeath::GrenadeSplash
+            | CauseOfDeath::Rocket
+            | CauseOfDeath::RocketSplash
+            | CauseOfDeath::Plasma
+            | CauseOfDeath::PlasmaSplash
+            | CauseOfDeath::Railgun
+            | CauseOfDeath::Lightning
+            | CauseOfDeath::Bfg
+            | CauseOfDeath::BfgSplash
+            | CauseOfDeath::Grapple => DeathCategory::Weapon,
+            CauseOfDeath::Water
+            | CauseOfDeath::Slime
+            | CauseOfDeath::Lava
+            | CauseOfDeath::Crush
+            | CauseOfDeath::Falling
+            | CauseOfDeath::TargetLaser
+            | CauseOfDeath::TriggerHurt => {
+                DeathCategory::Environment
+            }
+            CauseOfDeath::Suicide => {
+                DeathCategory::SelfInflicted
+            }
+            CauseOfDeath::Telefrag => DeathCategory::Telefrag,
+            CauseOfDeath::Nail
+            | CauseOfDeath::Chaingun
+            | CauseOfDeath::ProximityMine
+            | CauseOfDeath::Kamikaze
+            | CauseOfDeath::Juiced => DeathCategory::TeamArena,
+            CauseOfDeath::Unknown => DeathCategory::Unknown,
+        }
+    }
+}
+
+/// Rolls up the causes of death of a game or a player.
+impl InstanceCounter<CauseOfDeath, CAUSES_OF_DEATH> {
+    /// Adds up the deaths caused by each weapon, leaving out
+    /// the ones no weapon caused.
+    pub fn by_weapon(&self) -> crate::Result<WeaponCounter> {
+        let mut weapons = InstanceCounter::new();
+
+        for (cause, count) in self.iter() {
+            if let Some(weapon) = cause.weapon() {
+                weapons.add_count(weapon, count)?;
+            }
+        }
+
+        Ok(weapons)
+    }
+
+    /// Adds up the deaths of each category.
+    pub fn by_category(
+        &self,
+    ) -> crate::Result<DeathCategoryCounter> {
+        let mut categories = InstanceCounter::new();
+
+        for (cause, count) in self.iter() {
+            categories.add_count(cause.category(), count)?;
+        }
+
+        Ok(categories)
+    }
+}
+
+/// Broad kinds of causes of death
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(test, derive(strum::EnumIter, strum::EnumCount))]
+#[repr(u8)]
+pub enum DeathCategory {
+    /// Killed by one of the weapons of Q3A itself.
+    Weapon,
+    /// Killed by the map, such as by falling, drowning or lava.
+    Environment,
+    /// Killed themselves through the `kill` command.
+    SelfInflicted,
+    /// Spawned or teleported onto by another player.
+    Telefrag,
+    /// Killed by the weapons and items of the Team Arena
+    /// expansion.
+    TeamArena,
+    /// Killed in a way the game itself couldn't tell.
+    Unknown,
+}
+
+impl DeathCategory {
+    /// Converts a category into the name it's reported by
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeathCategory::Weapon => "weapon",
+            DeathCategory::Environment => "environment",
+            DeathCategory::SelfInflicted => "self",
+            DeathCategory::Telefrag => "telefrag",
+            DeathCategory::TeamArena => "team_arena",
+            DeathCategory::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<DeathCategory> for u8 {
+    fn from(category: DeathCategory) -> Self {
+        category as Self
+    }
+}
+
+impl TryFrom<u8> for DeathCategory {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let category = match value {
+            0 => DeathCategory::Weapon,
+            1 => DeathCategory::Environment,
+            2 => DeathCategory::SelfInflicted,
+            3 => DeathCategory::Telefrag,
+            4 => DeathCategory::TeamArena,
+            5 => DeathCategory::Unknown,
+            _ => {
+                return Err(Error::DeathCategoryFromByte(value))
+            }
+        };
+
+        Ok(category)
+    }
+}
+
+impl Display for DeathCategory {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for DeathCategory {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
 impl From<CauseOfDeath> for u8 {
@@ -195,11 +402,21 @@ impl Display for CauseOfDeath {
     }
 }
 
+impl Serialize for CauseOfDeath {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use strum::{EnumCount, IntoEnumIterator};
 
-    use super::{CauseOfDeath, CAUSES_OF_DEATH};
+    use super::{CauseOfDeath, DeathCategory, CAUSES_OF_DEATH};
+    use crate::{CauseOfDeathCounter, Weapon};
 
     /// Converts a mean of death into the expected
     /// Quake format
@@ -275,4 +492,65 @@ mod tests {
             assert_eq!(cause_of_death, expected_cause);
         }
     }
+
+    #[test]
+    fn check_death_category_u8_round_trip() {
+        for (idx, expected_category) in
+            (0..DeathCategory::COUNT).zip(DeathCategory::iter())
+        {
+            let category =
+                DeathCategory::try_from(idx as u8).unwrap();
+            let byte: u8 = category.into();
+
+            assert_eq!(category, expected_category);
+            assert_eq!(byte as usize, idx);
+        }
+
+        assert!(DeathCategory::try_from(
+            DeathCategory::COUNT as u8
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn groups_causes_of_death_by_weapon() {
+        assert_eq!(
+            CauseOfDeath::RocketSplash.weapon(),
+            Some(Weapon::RocketLauncher)
+        );
+        assert_eq!(CauseOfDeath::Lava.weapon(), None);
+
+        // Every weapon of the base game is in the weapon
+        // category
+        for cause in CauseOfDeath::iter() {
+            if cause.category() == DeathCategory::Weapon {
+                assert!(cause.weapon().is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn rolls_up_causes_of_death() {
+        let mut counter = CauseOfDeathCounter::new();
+
+        counter.add(CauseOfDeath::Rocket).unwrap();
+        counter.add(CauseOfDeath::RocketSplash).unwrap();
+        counter.add(CauseOfDeath::Falling).unwrap();
+        counter.add(CauseOfDeath::Lava).unwrap();
+
+        let weapons = counter.by_weapon().unwrap();
+        assert_eq!(weapons.get(Weapon::RocketLauncher), Some(2));
+        assert_eq!(weapons.total().unwrap(), 2);
+
+        let categories = counter.by_category().unwrap();
+        assert_eq!(
+            categories.get(DeathCategory::Weapon),
+            Some(2)
+        );
+        assert_eq!(
+            categories.get(DeathCategory::Environment),
+            Some(2)
+        );
+        assert_eq!(categories.total().unwrap(), 4);
+    }
 }
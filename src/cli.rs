@@ -0,0 +1,106 @@
+use std::{ffi::OsString, path::PathBuf, str::FromStr};
+
+use q3a_muncher::{
+    Error, OutputFormat, ParseMode, PlayerOrder, Result,
+};
+
+/// The command-line arguments given to `q3a-muncher`
+pub struct Args {
+    /// The log files to be parsed, in order. `-` stands for the
+    /// standard input.
+    pub paths: Vec<PathBuf>,
+    /// How to write out the game reports.
+    pub format: OutputFormat,
+    /// The order in which players are listed in reports.
+    pub player_order: PlayerOrder,
+    /// What to do with malformed lines.
+    pub mode: ParseMode,
+    /// Whether to keep reading the log as it's written to.
+    pub follow: bool,
+}
+
+impl Args {
+    /// Parses the arguments this process was started with.
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args_os().skip(1))
+    }
+
+    fn parse(
+        args: impl IntoIterator<Item = OsString>,
+    ) -> Result<Self> {
+        let mut args = args.into_iter();
+        let mut paths = Vec::new();
+        let mut format = None;
+        let mut player_order = PlayerOrder::default();
+        // Malformed lines are skipped unless asked otherwise
+        let mut mode = ParseMode::Lenient;
+        let mut follow = false;
+
+        while let Some(arg) = args.next() {
+            match arg.to_str() {
+                Some(flag @ "--format") => {
+                    format =
+                        Some(parse_value(flag, args.next())?);
+                }
+                Some(flag @ "--sort") => {
+                    player_order =
+                        parse_value(flag, args.next())?;
+                }
+                Some("--strict") => mode = ParseMode::Strict,
+                Some("--follow") => follow = true,
+                Some(flag) if flag.starts_with("--") => {
+                    return Err(Error::InvalidArgument(format!(
+                        "unknown flag `{flag}`"
+                    )))
+                }
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        if paths.is_empty() {
+            return Err(Error::MissingFile);
+        }
+
+        if follow
+            && (paths.len() > 1 || paths[0].as_os_str() == "-")
+        {
+            return Err(Error::InvalidArgument(
+                "--follow takes a single log file".into(),
+            ));
+        }
+
+        // A JSON object can only be closed once the log is over,
+        // which never happens when following it
+        let format = match (follow, format) {
+            (true, Some(OutputFormat::Json)) => {
+                return Err(Error::InvalidArgument(
+                    "--follow requires --format ndjson".into(),
+                ))
+            }
+            (true, None) => OutputFormat::Ndjson,
+            (_, format) => format.unwrap_or_default(),
+        };
+
+        Ok(Self {
+            paths,
+            format,
+            player_order,
+            mode,
+            follow,
+        })
+    }
+}
+
+/// Parses the value given to `flag`.
+fn parse_value<T: FromStr<Err = Error>>(
+    flag: &str,
+    value: Option<OsString>,
+) -> Result<T> {
+    let value = value.ok_or_else(|| {
+        Error::InvalidArgument(format!(
+            "{flag} requires a value"
+        ))
+    })?;
+
+    value.to_str().unwrap_or_default().parse()
+}
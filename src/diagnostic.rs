@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use crate::error::write_snippet;
+
+/// What to do with lines that can't be parsed
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ParseMode {
+    /// Stop at the first malformed line, returning its error.
+    #[default]
+    Strict,
+    /// Skip malformed lines, recording a [`Diagnostic`] for
+    /// each of them.
+    Lenient,
+}
+
+/// A line that was skipped for being malformed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The log the line belongs to, if it was named.
+    pub source: Option<String>,
+    /// The 1-based number of the line.
+    pub line: usize,
+    /// The offset, in bytes, at which the line starts.
+    pub offset: usize,
+    /// The 1-based column, in bytes of the log, at which
+    /// parsing failed, if the failure can be narrowed down
+    /// to a spot of the line.
+    pub column: Option<usize>,
+    /// The same column, in characters of `raw_line`.
+    pub char_column: Option<usize>,
+    /// Why the line could not be parsed.
+    pub reason: String,
+    /// The line itself, without its line terminator.
+    pub raw_line: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{source}, ")?;
+        }
+
+        write!(
+            f,
+            "line {} (byte {}): {}",
+            self.line, self.offset, self.reason
+        )?;
+
+        write_snippet(
+            f,
+            self.line,
+            &self.raw_line,
+            self.char_column,
+        )
+    }
+}
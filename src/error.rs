@@ -1,5 +1,17 @@
+use std::fmt::{self, Display};
+
+use nom::error::ErrorKind;
+
+use crate::GameReport;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// How to invoke the `q3a-muncher` binary
+const USAGE: &str =
+    "Usage: ./q3a-muncher [--format json|ndjson] \
+     [--sort first-seen|score|name] [--strict] [--follow] LOG-FILE...\n\
+     Pass `-` as a LOG-FILE to read from stdin";
+
 #[macro_export]
 /// Ensure a condition is true or early return
 /// with an `AssertionError`
@@ -23,12 +35,155 @@ pub enum Error {
     Assertion(&'static str),
     #[error("Unknown cause of death: {0}")]
     UnknownCauseOfDeath(String),
+    #[error("Invalid value for `{key}`: {value}")]
+    InvalidInfoValue { key: &'static str, value: String },
     #[error("No cause of death is mapped to {0}")]
     CauseOfDeathFromByte(u8),
-    #[error("Parsing error: {0}")]
-    ParsingError(#[from] nom::error::Error<String>),
-    #[error("Missing file\nUsage: ./q3a-muncher [LOG-FILE]")]
+    #[error("Unknown item: {0}")]
+    UnknownItem(String),
+    #[error("No item is mapped to {0}")]
+    ItemFromByte(u8),
+    #[error("No weapon is mapped to {0}")]
+    WeaponFromByte(u8),
+    #[error("No category of death is mapped to {0}")]
+    DeathCategoryFromByte(u8),
+    #[error("Parsing error at {0}")]
+    ParsingError(ParseError),
+    #[error("Invalid line at {0}")]
+    InvalidLine(InvalidLineError),
+    #[error("{error}")]
+    GameEnded {
+        report: Box<GameReport>,
+        error: Box<Error>,
+    },
+    #[error("Missing file\n{USAGE}")]
     MissingFile,
+    #[error("Invalid argument: {0}\n{USAGE}")]
+    InvalidArgument(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
+
+/// Where a line of a log failed to parse
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The log the offending line belongs to, if it was named.
+    pub source: Option<String>,
+    /// The 1-based number of the offending line.
+    pub line: usize,
+    /// The 1-based column, in bytes of the log, at which
+    /// parsing failed.
+    pub column: usize,
+    /// The 1-based column, in characters of `line_text`, at
+    /// which parsing failed.
+    pub char_column: usize,
+    /// The offset, in bytes, of the failing position within the
+    /// log.
+    pub offset: usize,
+    /// The offending line, without its line terminator.
+    pub line_text: String,
+    /// What the parser was trying to match when it failed.
+    pub kind: ErrorKind,
+}
+
+impl ParseError {
+    /// A short description of what went wrong.
+    pub fn reason(&self) -> String {
+        match self.kind {
+            ErrorKind::Eof => "unexpected trailing input".into(),
+            kind => {
+                format!(
+                    "unexpected input ({})",
+                    kind.description()
+                )
+            }
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{source}, ")?;
+        }
+
+        write!(
+            f,
+            "line {}, column {} (byte {}): {}",
+            self.line,
+            self.column,
+            self.offset,
+            self.reason()
+        )?;
+
+        write_snippet(
+            f,
+            self.line,
+            &self.line_text,
+            Some(self.char_column),
+        )
+    }
+}
+
+/// Where a line of a log holds a value that's well-formed, but
+/// invalid
+#[derive(Debug)]
+pub struct InvalidLineError {
+    /// The log the offending line belongs to, if it was named.
+    pub source: Option<String>,
+    /// The 1-based number of the offending line.
+    pub line: usize,
+    /// The offset, in bytes, at which the line starts.
+    pub offset: usize,
+    /// The offending line, without its line terminator.
+    pub line_text: String,
+    /// What's wrong with the line.
+    pub error: Box<Error>,
+}
+
+impl Display for InvalidLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{source}, ")?;
+        }
+
+        write!(
+            f,
+            "line {} (byte {}): {}",
+            self.line, self.offset, self.error
+        )?;
+
+        write_snippet(f, self.line, &self.line_text, None)
+    }
+}
+
+/// Writes out a line of a log below a gutter holding its line
+/// number, with a caret under the given 1-based column, in
+/// characters, e.g.
+///
+/// ```text
+///   |
+/// 3 |  2:11 Kill: 2 3 7: Zeh killed Mal by MOD_BANANA
+///   |                                      ^
+/// ```
+pub(crate) fn write_snippet(
+    f: &mut fmt::Formatter<'_>,
+    line: usize,
+    text: &str,
+    column: Option<usize>,
+) -> fmt::Result {
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+
+    write!(f, "\n{gutter} |\n{number} | {text}")?;
+
+    if let Some(column) = column {
+        let padding = column.saturating_sub(1);
+
+        write!(f, "\n{gutter} | {:padding$}^", "")?;
+    }
+
+    Ok(())
+}
@@ -2,33 +2,49 @@ use num_traits::{CheckedAdd, CheckedSub, Num, One};
 
 use crate::{Error, Result};
 
-pub trait ExtraCheckedOps {
+pub trait ExtraCheckedOps: Sized {
     /// Increment the given value, erring on overflows.
     fn checked_increment(&mut self) -> Result<()>;
 
-    /// Decremetn the given value, erring on underflows.
-    fn checked_decrement(&mut self) -> Result<()>;
+    /// Add `amount` to the given value, erring on overflows.
+    fn checked_add_assign(&mut self, amount: Self)
+        -> Result<()>;
+
+    /// Subtract `amount` from the given value, erring on
+    /// underflows.
+    fn checked_sub_assign(&mut self, amount: Self)
+        -> Result<()>;
 }
 
 impl<N: Num + CheckedAdd + CheckedSub + One + Copy>
     ExtraCheckedOps for N
 {
     fn checked_increment(&mut self) -> Result<()> {
+        self.checked_add_assign(N::one())
+    }
+
+    fn checked_add_assign(
+        &mut self,
+        amount: Self,
+    ) -> Result<()> {
         let current = *self;
-        let one = N::one();
 
-        *self =
-            current.checked_add(&one).ok_or(Error::Overflow)?;
+        *self = current
+            .checked_add(&amount)
+            .ok_or(Error::Overflow)?;
 
         Ok(())
     }
 
-    fn checked_decrement(&mut self) -> Result<()> {
+    fn checked_sub_assign(
+        &mut self,
+        amount: Self,
+    ) -> Result<()> {
         let current = *self;
-        let one = N::one();
 
-        *self =
-            current.checked_sub(&one).ok_or(Error::Underflow)?;
+        *self = current
+            .checked_sub(&amount)
+            .ok_or(Error::Underflow)?;
 
         Ok(())
     }
@@ -46,9 +62,16 @@ mod tests {
         zero.checked_increment().unwrap();
         zero.checked_increment().unwrap();
         assert_eq!(zero, 2);
-        zero.checked_decrement().unwrap();
+        zero.checked_sub_assign(1).unwrap();
         assert_eq!(zero, 1);
 
         assert!(limit.checked_increment().is_err());
+
+        zero.checked_add_assign(200).unwrap();
+        assert_eq!(zero, 201);
+        assert!(zero.checked_add_assign(55).is_err());
+        zero.checked_sub_assign(201).unwrap();
+        assert_eq!(zero, 0);
+        assert!(zero.checked_sub_assign(1).is_err());
     }
 }
@@ -0,0 +1,293 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    reader::{decode_line, LogLine},
+    Result,
+};
+
+/// How long to wait for a log to grow by default.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A line reader that keeps up with a log as it's written to,
+/// in the spirit of `tail -F`.
+///
+/// Lines are only handed out once complete, so the ones still
+/// being written are held back until their line terminator
+/// comes in. The log being truncated, or rotated (replaced by a
+/// new file at the same path), is taken into account.
+pub struct LogFollower {
+    path: PathBuf,
+    reader: BufReader<File>,
+    /// Identifies the file being read, to find out when it gets
+    /// rotated.
+    file_id: Option<(u64, u64)>,
+    /// How many bytes of the file were read so far.
+    position: u64,
+    /// The line being read, as raw bytes.
+    bytes: Vec<u8>,
+    /// The last line handed out.
+    buffer: String,
+    /// Whether `bytes` holds a line that was already handed
+    /// out.
+    line_taken: bool,
+    poll_interval: Duration,
+}
+
+impl LogFollower {
+    /// Opens the log at the given path, to be read from its
+    /// start.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file = File::open(&path)?;
+        let file_id = file_id(&file.metadata()?);
+
+        Ok(Self {
+            path,
+            reader: BufReader::new(file),
+            file_id,
+            position: 0,
+            bytes: Vec::with_capacity(1024),
+            buffer: String::with_capacity(1024),
+            line_taken: false,
+            poll_interval: POLL_INTERVAL,
+        })
+    }
+
+    /// Sets how long to wait for the log to grow before checking
+    /// on it again.
+    pub fn with_poll_interval(
+        mut self,
+        interval: Duration,
+    ) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Reads the next line, waiting for it to be written if
+    /// needed.
+    ///
+    /// The returned line is only valid until the next call to
+    /// this function.
+    pub fn read_line(&mut self) -> Result<LogLine<'_>> {
+        while !self.poll()? {
+            thread::sleep(self.poll_interval);
+        }
+
+        Ok(self.take_line())
+    }
+
+    /// Reads the next line, returning `None` if it wasn't
+    /// entirely written yet.
+    pub fn try_read_line(
+        &mut self,
+    ) -> Result<Option<LogLine<'_>>> {
+        if self.poll()? {
+            Ok(Some(self.take_line()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads as much of the next line as is available, returning
+    /// whether it is complete.
+    fn poll(&mut self) -> Result<bool> {
+        if self.line_taken {
+            self.bytes.clear();
+            self.line_taken = false;
+        }
+
+        let bytes_read =
+            self.reader.read_until(b'\n', &mut self.bytes)?;
+        self.position += bytes_read as u64;
+
+        if self.bytes.ends_with(b"\n") {
+            return Ok(true);
+        }
+
+        // Caught up with the writer, so check whether the file
+        // is still the one being written to
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            // Rotated away, with the new file yet to be created
+            Err(err)
+                if err.kind() == io::ErrorKind::NotFound =>
+            {
+                return Ok(false)
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if file_id(&metadata) != self.file_id {
+            self.reopen()?;
+
+            // The old file won't grow anymore, so whatever was
+            // left of it makes up its last line
+            return Ok(!self.bytes.is_empty());
+        }
+
+        if metadata.len() < self.position {
+            // Truncated, so start over
+            self.reader.seek(SeekFrom::Start(0))?;
+            self.position = 0;
+            self.bytes.clear();
+        }
+
+        Ok(false)
+    }
+
+    fn reopen(&mut self) -> Result<()> {
+        let file = File::open(&self.path)?;
+
+        self.file_id = file_id(&file.metadata()?);
+        self.reader = BufReader::new(file);
+        self.position = 0;
+
+        Ok(())
+    }
+
+    fn take_line(&mut self) -> LogLine<'_> {
+        self.line_taken = true;
+
+        decode_line(&self.bytes, &mut self.buffer)
+    }
+}
+
+/// Identifies a file through its device and inode numbers.
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Files can't be told apart here, so rotation goes unnoticed.
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, OpenOptions},
+        io::Write,
+        path::PathBuf,
+    };
+
+    use super::LogFollower;
+
+    /// A log in the temporary directory, removed once dropped.
+    struct TempLog(PathBuf);
+
+    impl TempLog {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "q3a-muncher-{}-{name}.log",
+                std::process::id()
+            ));
+            fs::write(&path, "").unwrap();
+
+            Self(path)
+        }
+
+        fn append(&self, contents: &str) {
+            OpenOptions::new()
+                .append(true)
+                .open(&self.0)
+                .unwrap()
+                .write_all(contents.as_bytes())
+                .unwrap();
+        }
+    }
+
+    impl Drop for TempLog {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+            let _ = fs::remove_file(self.0.with_extension("1"));
+        }
+    }
+
+    fn try_read(follower: &mut LogFollower) -> Option<String> {
+        follower
+            .try_read_line()
+            .unwrap()
+            .map(|line| line.text.to_owned())
+    }
+
+    #[test]
+    fn holds_back_partial_lines() {
+        let log = TempLog::new("partial");
+        log.append(
+            "  0:01 Kill: 2 3 7: Zeh killed Mal\n  0:02 Shut",
+        );
+
+        let mut follower = LogFollower::open(&log.0).unwrap();
+
+        assert_eq!(
+            try_read(&mut follower).as_deref(),
+            Some("  0:01 Kill: 2 3 7: Zeh killed Mal\n")
+        );
+        assert_eq!(try_read(&mut follower), None);
+
+        log.append("downGame:\n");
+        assert_eq!(
+            try_read(&mut follower).as_deref(),
+            Some("  0:02 ShutdownGame:\n")
+        );
+        assert_eq!(try_read(&mut follower), None);
+    }
+
+    #[test]
+    fn starts_over_when_truncated() {
+        let log = TempLog::new("truncated");
+        log.append("  0:01 InitGame:\n  0:02 ShutdownGame:\n");
+
+        let mut follower = LogFollower::open(&log.0).unwrap();
+        while try_read(&mut follower).is_some() {}
+
+        fs::write(&log.0, "  0:00 Exit:\n").unwrap();
+
+        assert_eq!(try_read(&mut follower), None);
+        assert_eq!(
+            try_read(&mut follower).as_deref(),
+            Some("  0:00 Exit:\n")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_rotated_logs() {
+        let log = TempLog::new("rotated");
+        log.append("  0:01 InitGame:\n");
+
+        let mut follower = LogFollower::open(&log.0).unwrap();
+        assert!(try_read(&mut follower).is_some());
+
+        // Written right before the log got rotated
+        log.append("  0:02 ShutdownGame:\n  0:03 Exit:");
+        fs::rename(&log.0, log.0.with_extension("1")).unwrap();
+        fs::write(&log.0, "  0:00 InitGame:\n").unwrap();
+
+        // The rest of the old log comes first ..
+        assert_eq!(
+            try_read(&mut follower).as_deref(),
+            Some("  0:02 ShutdownGame:\n")
+        );
+        assert_eq!(
+            try_read(&mut follower).as_deref(),
+            Some("  0:03 Exit:")
+        );
+
+        // .. and then the new one
+        assert_eq!(
+            try_read(&mut follower).as_deref(),
+            Some("  0:00 InitGame:\n")
+        );
+    }
+}
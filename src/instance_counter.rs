@@ -1,23 +1,45 @@
 use std::marker::PhantomData;
 
+use num_traits::{CheckedAdd, CheckedSub, Num};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
 use crate::{
-    ensure, extra_checked_ops::ExtraCheckedOps, Result,
+    ensure, extra_checked_ops::ExtraCheckedOps, Error, Result,
 };
 
-pub struct InstanceCounter<T: Into<u8>, const N: usize> {
-    counter: [u8; N],
+/// Counts how many times each variant of `T` was seen.
+///
+/// `N` must be the amount of variants of `T`, and `C` is the
+/// integer type each variant is counted with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstanceCounter<T: Into<u8>, const N: usize, C = u32>
+{
+    counter: [C; N],
     marker: PhantomData<T>,
 }
 
-impl<T: Into<u8>, const N: usize> InstanceCounter<T, N> {
-    pub const fn new() -> Self {
+impl<T, const N: usize, C> InstanceCounter<T, N, C>
+where
+    T: Into<u8>,
+    C: Num + CheckedAdd + CheckedSub + Copy,
+{
+    pub fn new() -> Self {
         Self {
-            counter: [0; N],
+            counter: [C::zero(); N],
             marker: PhantomData,
         }
     }
 
     pub fn add(&mut self, element: T) -> Result<()> {
+        self.add_count(element, C::one())
+    }
+
+    /// Counts `amount` more instances of `element` at once.
+    pub fn add_count(
+        &mut self,
+        element: T,
+        amount: C,
+    ) -> Result<()> {
         let position = Self::element_to_usize(element);
 
         ensure!(
@@ -25,17 +47,52 @@ impl<T: Into<u8>, const N: usize> InstanceCounter<T, N> {
             "Element would not fit in the counter buffer"
         );
 
-        self.counter[position].checked_increment()?;
-
-        Ok(())
+        self.counter[position].checked_add_assign(amount)
     }
 
-    pub fn get(&self, element: T) -> Option<u8> {
+    pub fn get(&self, element: T) -> Option<C> {
         let index = Self::element_to_usize(element);
 
         self.counter.get(index).copied()
     }
 
+    /// Adds up the incidences of `other` into this counter.
+    pub fn merge(&mut self, other: &Self) -> Result<()> {
+        for (count, &other) in
+            self.counter.iter_mut().zip(&other.counter)
+        {
+            *count = count
+                .checked_add(&other)
+                .ok_or(Error::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    /// How many elements were counted, all variants included.
+    pub fn total(&self) -> Result<C> {
+        self.counter.iter().try_fold(
+            C::zero(),
+            |total, count| {
+                total.checked_add(count).ok_or(Error::Overflow)
+            },
+        )
+    }
+
+    /// Iterates over every variant along with its incidence, in
+    /// the order given by their `u8` representations.
+    pub fn iter(&self) -> impl Iterator<Item = (T, C)> + '_
+    where
+        T: TryFrom<u8>,
+    {
+        self.counter.iter().enumerate().filter_map(
+            |(idx, &count)| {
+                let element = T::try_from(idx as u8).ok()?;
+                Some((element, count))
+            },
+        )
+    }
+
     #[inline(always)]
     fn element_to_usize(element: T) -> usize {
         let byte: u8 = element.into();
@@ -43,6 +100,47 @@ impl<T: Into<u8>, const N: usize> InstanceCounter<T, N> {
     }
 }
 
+impl<T, const N: usize, C> Default for InstanceCounter<T, N, C>
+where
+    T: Into<u8>,
+    C: Num + CheckedAdd + CheckedSub + Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serializes the counter as a map from every possible element
+/// to its incidence, in the order given by their `u8`
+/// representations.
+impl<T, const N: usize, C> Serialize for InstanceCounter<T, N, C>
+where
+    T: Into<u8> + TryFrom<u8> + Serialize,
+    C: Serialize,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+
+        let mut map = serializer.serialize_map(Some(N))?;
+
+        for (idx, incidence) in self.counter.iter().enumerate() {
+            let element =
+                T::try_from(idx as u8).map_err(|_| {
+                    S::Error::custom(format!(
+                        "no element is mapped to {idx}"
+                    ))
+                })?;
+
+            map.serialize_entry(&element, incidence)?;
+        }
+
+        map.end()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::InstanceCounter;
@@ -64,4 +162,60 @@ mod tests {
             0
         );
     }
+
+    #[test]
+    fn instance_counter_counts_past_u8() {
+        let mut counter: InstanceCounter<
+            CauseOfDeath,
+            CAUSES_OF_DEATH,
+        > = InstanceCounter::new();
+
+        for _ in 0..300 {
+            counter.add(CauseOfDeath::Rocket).unwrap();
+        }
+
+        assert_eq!(counter.get(CauseOfDeath::Rocket), Some(300));
+
+        // Narrower counters still catch overflows
+        let mut counter: InstanceCounter<
+            CauseOfDeath,
+            CAUSES_OF_DEATH,
+            u8,
+        > = InstanceCounter::new();
+
+        for _ in 0..255 {
+            counter.add(CauseOfDeath::Rocket).unwrap();
+        }
+
+        assert!(counter.add(CauseOfDeath::Rocket).is_err());
+    }
+
+    #[test]
+    fn instance_counter_merges_and_totals() {
+        let mut first: InstanceCounter<
+            CauseOfDeath,
+            CAUSES_OF_DEATH,
+        > = InstanceCounter::new();
+        let mut second = first.clone();
+
+        first.add(CauseOfDeath::Rocket).unwrap();
+        first.add(CauseOfDeath::Lava).unwrap();
+        second.add(CauseOfDeath::Rocket).unwrap();
+
+        first.merge(&second).unwrap();
+
+        assert_eq!(first.get(CauseOfDeath::Rocket), Some(2));
+        assert_eq!(first.get(CauseOfDeath::Lava), Some(1));
+        assert_eq!(first.total().unwrap(), 3);
+
+        let seen: Vec<_> = first
+            .iter()
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        assert_eq!(
+            seen,
+            [(CauseOfDeath::Rocket, 2), (CauseOfDeath::Lava, 1)]
+        );
+        assert_eq!(first.iter().count(), CAUSES_OF_DEATH);
+    }
 }
@@ -0,0 +1,402 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Serialize, Serializer};
+
+/// How many items there are.
+pub const ITEMS: usize = 51;
+
+#[cfg(test)]
+use strum::EnumCount;
+
+use crate::{Error, InstanceCounter};
+#[cfg(test)]
+// Ensure `ITEMS` is correct
+static_assertions::const_assert_eq!(ITEMS, Item::COUNT);
+
+/// Counts how many times each item was picked up.
+pub type ItemCounter = InstanceCounter<Item, ITEMS>;
+
+/// The items that can be picked up in Q3A and its Team Arena
+/// expansion, as logged by lines such as
+/// `Item: 2 weapon_rocketlauncher`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(test, derive(strum::EnumIter, strum::EnumCount))]
+#[repr(u8)]
+pub enum Item {
+    ArmorShard,
+    ArmorCombat,
+    ArmorBody,
+    HealthSmall,
+    Health,
+    HealthLarge,
+    HealthMega,
+    Gauntlet,
+    Shotgun,
+    Machinegun,
+    GrenadeLauncher,
+    RocketLauncher,
+    LightningGun,
+    Railgun,
+    PlasmaGun,
+    Bfg,
+    GrapplingHook,
+    Shells,
+    Bullets,
+    Grenades,
+    Cells,
+    Lightning,
+    Rockets,
+    Slugs,
+    BfgAmmo,
+    Teleporter,
+    Medkit,
+    Quad,
+    BattleSuit,
+    Haste,
+    Invisibility,
+    Regeneration,
+    Flight,
+    RedFlag,
+    BlueFlag,
+    Kamikaze,
+    Portal,
+    Invulnerability,
+    Nails,
+    Mines,
+    Belt,
+    Scout,
+    Guard,
+    Doubler,
+    AmmoRegen,
+    NeutralFlag,
+    RedCube,
+    BlueCube,
+    Nailgun,
+    ProxLauncher,
+    Chaingun,
+}
+
+/// Broad kinds of items
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ItemKind {
+    Armor,
+    Health,
+    Weapon,
+    Ammo,
+    /// Items that are kept until used, such as the medkit.
+    Holdable,
+    /// Power-ups such as quad damage, haste or regeneration,
+    /// including the persistent ones of Team Arena.
+    Powerup,
+    /// CTF flags, along with the skulls of Harvester games.
+    Flag,
+}
+
+impl Item {
+    /// Converts an item into its Q3A class name
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Item::ArmorShard => "item_armor_shard",
+            Item::ArmorCombat => "item_armor_combat",
+            Item::ArmorBody => "item_armor_body",
+            Item::HealthSmall => "item_health_small",
+            Item::Health => "item_health",
+            Item::HealthLarge => "item_health_large",
+            Item::HealthMega => "item_health_mega",
+            Item::Gauntlet => "weapon_gauntlet",
+            Item::Shotgun => "weapon_shotgun",
+            Item::Machinegun => "weapon_machinegun",
+            Item::GrenadeLauncher => "weapon_grenadelauncher",
+            Item::RocketLauncher => "weapon_rocketlauncher",
+            Item::LightningGun => "weapon_lightning",
+            Item::Railgun => "weapon_railgun",
+            Item::PlasmaGun => "weapon_plasmagun",
+            Item::Bfg => "weapon_bfg",
+            Item::GrapplingHook => "weapon_grapplinghook",
+            Item::Shells => "ammo_shells",
+            Item::Bullets => "ammo_bullets",
+            Item::Grenades => "ammo_grenades",
+            Item::Cells => "ammo_cells",
+            Item::Lightning => "ammo_lightning",
+            Item::Rockets => "ammo_rockets",
+            Item::Slugs => "ammo_slugs",
+            Item::BfgAmmo => "ammo_bfg",
+            Item::Teleporter => "holdable_teleporter",
+            Item::Medkit => "holdable_medkit",
+            Item::Quad => "item_quad",
+            Item::BattleSuit => "item_enviro",
+            Item::Haste => "item_haste",
+            Item::Invisibility => "item_invis",
+            Item::Regeneration => "item_regen",
+            Item::Flight => "item_flight",
+            Item::RedFlag => "team_CTF_redflag",
+            Item::BlueFlag => "team_CTF_blueflag",
+            Item::Kamikaze => "holdable_kamikaze",
+            Item::Portal => "holdable_portal",
+            Item::Invulnerability => "holdable_invulnerability",
+            Item::Nails => "ammo_nails",
+            Item::Mines => "ammo_mines",
+            Item::Belt => "ammo_belt",
+            Item::Scout => "item_scout",
+            Item::Guard => "item_guard",
+            Item::Doubler => "item_doubler",
+            Item::AmmoRegen => "item_ammoregen",
+            Item::NeutralFlag => "team_CTF_neutralflag",
+            Item::RedCube => "item_redcube",
+            Item::BlueCube => "item_bluecube",
+            Item::Nailgun => "weapon_nailgun",
+            Item::ProxLauncher => "weapon_prox_launcher",
+            Item::Chaingun => "weapon_chaingun",
+        }
+    }
+
+    /// What kind of item this is.
+    pub fn kind(self) -> ItemKind {
+        match self {
+            Item::ArmorShard
+            | Item::ArmorCombat
+            | Item::ArmorBody => ItemKind::Armor,
+            Item::HealthSmall
+            | Item::Health
+            | Item::HealthLarge
+            | Item::HealthMega => ItemKind::Health,
+            Item::Gauntlet
+            | Item::Shotgun
+            | Item::Machinegun
+            | Item::GrenadeLauncher
+            | Item::RocketLauncher
+            | Item::LightningGun
+            | Item::Railgun
+            | Item::PlasmaGun
+            | Item::Bfg
+            | Item::GrapplingHook
+            | Item::Nailgun
+            | Item::ProxLauncher
+            | Item::Chaingun => ItemKind::Weapon,
+            Item::Shells
+            | Item::Bullets
+            | Item::Grenades
+            | Item::Cells
+            | Item::Lightning
+            | Item::Rockets
+            | Item::Slugs
+            | Item::BfgAmmo
+            | Item::Nails
+            | Item::Mines
+            | Item::Belt => ItemKind::Ammo,
+            Item::Teleporter
+            | Item::Medkit
+            | Item::Kamikaze
+            | Item::Portal
+            | Item::Invulnerability => ItemKind::Holdable,
+            Item::Quad
+            | Item::BattleSuit
+            | Item::Haste
+            | Item::Invisibility
+            | Item::Regeneration
+            | Item::Flight
+            | Item::Scout
+            | Item::Guard
+            | Item::Doubler
+            | Item::AmmoRegen => ItemKind::Powerup,
+            Item::RedFlag
+            | Item::BlueFlag
+            | Item::NeutralFlag
+            | Item::RedCube
+            | Item::BlueCube => ItemKind::Flag,
+        }
+    }
+}
+
+impl From<Item> for u8 {
+    fn from(item: Item) -> Self {
+        item as Self
+    }
+}
+
+impl TryFrom<u8> for Item {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let item = match value {
+            0 => Item::ArmorShard,
+            1 => Item::ArmorCombat,
+            2 => Item::ArmorBody,
+            3 => Item::HealthSmall,
+            4 => Item::Health,
+            5 => Item::HealthLarge,
+            6 => Item::HealthMega,
+            7 => Item::Gauntlet,
+            8 => Item::Shotgun,
+            9 => Item::Machinegun,
+            10 => Item::GrenadeLauncher,
+            11 => Item::RocketLauncher,
+            12 => Item::LightningGun,
+            13 => Item::Railgun,
+            14 => Item::PlasmaGun,
+            15 => Item::Bfg,
+            16 => Item::GrapplingHook,
+            17 => Item::Shells,
+            18 => Item::Bullets,
+            19 => Item::Grenades,
+            20 => Item::Cells,
+            21 => Item::Lightning,
+            22 => Item::Rockets,
+            23 => Item::Slugs,
+            24 => Item::BfgAmmo,
+            25 => Item::Teleporter,
+            26 => Item::Medkit,
+            27 => Item::Quad,
+            28 => Item::BattleSuit,
+            29 => Item::Haste,
+            30 => Item::Invisibility,
+            31 => Item::Regeneration,
+            32 => Item::Flight,
+            33 => Item::RedFlag,
+            34 => Item::BlueFlag,
+            35 => Item::Kamikaze,
+            36 => Item::Portal,
+            37 => Item::Invulnerability,
+            38 => Item::Nails,
+            39 => Item::Mines,
+            40 => Item::Belt,
+            41 => Item::Scout,
+            42 => Item::Guard,
+            43 => Item::Doubler,
+            44 => Item::AmmoRegen,
+            45 => Item::NeutralFlag,
+            46 => Item::RedCube,
+            47 => Item::BlueCube,
+            48 => Item::Nailgun,
+            49 => Item::ProxLauncher,
+            50 => Item::Chaingun,
+            _ => return Err(Error::ItemFromByte(value)),
+        };
+
+        Ok(item)
+    }
+}
+
+impl FromStr for Item {
+    type Err = crate::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let item = match input {
+            "item_armor_shard" => Item::ArmorShard,
+            "item_armor_combat" => Item::ArmorCombat,
+            "item_armor_body" => Item::ArmorBody,
+            "item_health_small" => Item::HealthSmall,
+            "item_health" => Item::Health,
+            "item_health_large" => Item::HealthLarge,
+            "item_health_mega" => Item::HealthMega,
+            "weapon_gauntlet" => Item::Gauntlet,
+            "weapon_shotgun" => Item::Shotgun,
+            "weapon_machinegun" => Item::Machinegun,
+            "weapon_grenadelauncher" => Item::GrenadeLauncher,
+            "weapon_rocketlauncher" => Item::RocketLauncher,
+            "weapon_lightning" => Item::LightningGun,
+            "weapon_railgun" => Item::Railgun,
+            "weapon_plasmagun" => Item::PlasmaGun,
+            "weapon_bfg" => Item::Bfg,
+            "weapon_grapplinghook" => Item::GrapplingHook,
+            "ammo_shells" => Item::Shells,
+            "ammo_bullets" => Item::Bullets,
+            "ammo_grenades" => Item::Grenades,
+            "ammo_cells" => Item::Cells,
+            "ammo_lightning" => Item::Lightning,
+            "ammo_rockets" => Item::Rockets,
+            "ammo_slugs" => Item::Slugs,
+            "ammo_bfg" => Item::BfgAmmo,
+            "holdable_teleporter" => Item::Teleporter,
+            "holdable_medkit" => Item::Medkit,
+            "item_quad" => Item::Quad,
+            "item_enviro" => Item::BattleSuit,
+            "item_haste" => Item::Haste,
+            "item_invis" => Item::Invisibility,
+            "item_regen" => Item::Regeneration,
+            "item_flight" => Item::Flight,
+            "team_CTF_redflag" => Item::RedFlag,
+            "team_CTF_blueflag" => Item::BlueFlag,
+            "holdable_kamikaze" => Item::Kamikaze,
+            "holdable_portal" => Item::Portal,
+            "holdable_invulnerability" => Item::Invulnerability,
+            "ammo_nails" => Item::Nails,
+            "ammo_mines" => Item::Mines,
+            "ammo_belt" => Item::Belt,
+            "item_scout" => Item::Scout,
+            "item_guard" => Item::Guard,
+            "item_doubler" => Item::Doubler,
+            "item_ammoregen" => Item::AmmoRegen,
+            "team_CTF_neutralflag" => Item::NeutralFlag,
+            "item_redcube" => Item::RedCube,
+            "item_bluecube" => Item::BlueCube,
+            "weapon_nailgun" => Item::Nailgun,
+            "weapon_prox_launcher" => Item::ProxLauncher,
+            "weapon_chaingun" => Item::Chaingun,
+            _ => return Err(Error::UnknownItem(input.into())),
+        };
+
+        Ok(item)
+    }
+}
+
+impl Display for Item {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Item {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::{EnumCount, IntoEnumIterator};
+
+    use super::{Item, ItemKind};
+
+    #[test]
+    fn parses_items_from_str() {
+        for item in Item::iter() {
+            assert_eq!(
+                item.as_str().parse::<Item>().unwrap(),
+                item
+            );
+        }
+
+        assert!("item_banana".parse::<Item>().is_err());
+    }
+
+    #[test]
+    fn check_u8_round_trip() {
+        for (idx, expected_item) in
+            (0..Item::COUNT).zip(Item::iter())
+        {
+            let item = Item::try_from(idx as u8).unwrap();
+            let byte: u8 = item.into();
+
+            assert_eq!(item, expected_item);
+            assert_eq!(byte as usize, idx);
+        }
+    }
+
+    #[test]
+    fn groups_items_by_kind() {
+        assert_eq!(
+            Item::RocketLauncher.kind(),
+            ItemKind::Weapon
+        );
+        assert_eq!(Item::Rockets.kind(), ItemKind::Ammo);
+        assert_eq!(Item::Quad.kind(), ItemKind::Powerup);
+        assert_eq!(Item::RedFlag.kind(), ItemKind::Flag);
+    }
+}
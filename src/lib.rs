@@ -0,0 +1,54 @@
+//! `q3a-muncher` parses through Quake 3 Arena logfiles and
+//! builds reports for every game.
+//!
+//! The main entry point is [`LogParser`], which consumes a log
+//! one line at a time and yields a [`GameReport`] whenever a
+//! game ends. [`ReallocBufReader`] can be used to feed it lines
+//! from a file without allocating a new `String` per line.
+//!
+//! [`Events`] turns any log into a stream of typed [`Event`]s
+//! instead, for consumers other than reports.
+
+mod cause_of_death;
+mod diagnostic;
+mod error;
+mod extra_checked_ops;
+mod follow;
+mod instance_counter;
+mod item;
+mod output;
+mod parser;
+mod reader;
+mod registry;
+mod report;
+mod scoring;
+mod settings;
+mod warning;
+mod weapon;
+
+pub use cause_of_death::{
+    CauseOfDeath, DeathCategory, DeathCategoryCounter,
+    CAUSES_OF_DEATH, DEATH_CATEGORIES,
+};
+pub use diagnostic::{Diagnostic, ParseMode};
+pub use error::{Error, InvalidLineError, ParseError, Result};
+pub use follow::LogFollower;
+pub use instance_counter::InstanceCounter;
+pub use item::{Item, ItemCounter, ItemKind, ITEMS};
+pub use output::{OutputFormat, ReportWriter};
+pub use parser::{
+    CauseOfDeathCounter, Event, EventHandler, Events, Kill,
+    LogParser, ResolvedKill, TimedEvent,
+};
+pub use reader::{LogLine, ReallocBufReader};
+pub use registry::{
+    strip_color_codes, ClientId, ClientInfo, PlayerRegistry,
+    Team,
+};
+pub use report::{
+    GameReport, PlayerOrder, PlayerStats, ScoreboardEntry,
+};
+pub use scoring::ScoringRules;
+pub use settings::{GameSettings, GameType};
+pub use warning::Warning;
+pub use weapon::{Weapon, WeaponCounter, WEAPONS};
@@ -1,59 +1,139 @@
-mod cause_of_death;
-mod error;
-mod extra_checked_ops;
-mod instance_counter;
-mod parser;
+mod cli;
 
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    path::Path,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
 };
 
-pub use cause_of_death::{CauseOfDeath, CAUSES_OF_DEATH};
-pub use error::{Error, Result};
-use parser::LogParser;
+use q3a_muncher::{
+    Error, LogFollower, LogLine, LogParser, ReallocBufReader,
+    ReportWriter, Result,
+};
+
+use crate::cli::Args;
 
 fn run() -> Result<()> {
-    let path =
-        std::env::args_os().nth(1).ok_or(Error::MissingFile)?;
+    let args = Args::from_env()?;
 
-    let mut reader = ReallocBufReader::from(path)?;
-    let mut parser = LogParser::new();
+    let mut parser = LogParser::new()
+        .with_player_order(args.player_order)
+        .with_mode(args.mode);
+    let mut writer =
+        ReportWriter::new(io::stdout().lock(), args.format);
 
-    while let Some(line) = reader.read_line()? {
-        parser.parse_line(line)?;
+    if args.follow {
+        return follow(&args.paths[0], &mut parser, &mut writer);
     }
 
-    Ok(())
+    // The reports written so far are closed off even if parsing
+    // fails, so that the output remains valid JSON
+    let result =
+        parse_logs(&args.paths, &mut parser, &mut writer);
+    drop(writer.finish()?);
+
+    let diagnostics = parser.diagnostics();
+    if !diagnostics.is_empty() {
+        eprintln!(
+            "skipped {} malformed line(s):",
+            diagnostics.len()
+        );
+        for diagnostic in diagnostics {
+            eprintln!("{diagnostic}");
+        }
+    }
+
+    result
 }
 
-fn main() {
-    if let Err(err) = run() {
-        eprintln!("{err}");
+/// Parses the logs at the given paths in order, writing out the
+/// report of every game.
+fn parse_logs<W: Write>(
+    paths: &[PathBuf],
+    parser: &mut LogParser,
+    writer: &mut ReportWriter<W>,
+) -> Result<()> {
+    // Games carry on from one file to the next, as they may have
+    // been split up by log rotation
+    for path in paths {
+        let (mut reader, source) = if path.as_os_str() == "-" {
+            (ReallocBufReader::stdin()?, "<stdin>".into())
+        } else {
+            (
+                ReallocBufReader::from(path)?,
+                path.display().to_string(),
+            )
+        };
+
+        parser.set_source(source);
+
+        while let Some(line) = reader.read_line()? {
+            handle_line(parser, writer, line)?;
+        }
+    }
+
+    // The last game may have been cut short
+    if let Some(report) = parser.finish() {
+        writer.write(&report)?;
     }
+
+    Ok(())
 }
 
-struct ReallocBufReader {
-    reader: BufReader<File>,
-    buffer: String,
+/// Keeps parsing the log at the given path as the server writes
+/// to it, only returning if something goes wrong.
+fn follow<W: Write>(
+    path: &Path,
+    parser: &mut LogParser,
+    writer: &mut ReportWriter<W>,
+) -> Result<()> {
+    let mut follower = LogFollower::open(path)?;
+    parser.set_source(path.display().to_string());
+
+    loop {
+        let line = follower.read_line()?;
+        handle_line(parser, writer, line)?;
+
+        // There's no end of the log to sum them up at
+        for diagnostic in parser.take_diagnostics() {
+            eprintln!("skipped {diagnostic}");
+        }
+    }
 }
 
-impl ReallocBufReader {
-    pub fn from<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let buffer = String::with_capacity(1024);
+/// Parses a single line, writing out the report of the game it
+/// ended, if any.
+fn handle_line<W: Write>(
+    parser: &mut LogParser,
+    writer: &mut ReportWriter<W>,
+    line: LogLine,
+) -> Result<()> {
+    let report = match parser.parse_line(line) {
+        // Write out the game the line ended before failing
+        Err(Error::GameEnded { report, error }) => {
+            writer.write(&report)?;
+            return Err(*error);
+        }
+        result => result?,
+    };
 
-        Ok(Self { reader, buffer })
+    for warning in parser.take_warnings() {
+        eprintln!("warning: {warning}");
     }
 
-    pub fn read_line(&mut self) -> Result<Option<&str>> {
-        self.buffer.clear();
+    if let Some(report) = report {
+        writer.write(&report)?;
+    }
 
-        let bytes_read =
-            self.reader.read_line(&mut self.buffer)?;
+    Ok(())
+}
 
-        Ok((bytes_read != 0).then(|| self.buffer.as_str()))
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
     }
 }
@@ -0,0 +1,319 @@
+use std::{io::Write, str::FromStr};
+
+use crate::{Error, GameReport, Result};
+
+/// How game reports are written out
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OutputFormat {
+    /// A single JSON object mapping every game's key
+    /// (e.g. `"game0"`) to its report.
+    #[default]
+    Json,
+    /// Newline-delimited JSON: one `{"gameN": {...}}` object
+    /// per line.
+    Ndjson,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            _ => Err(Error::InvalidArgument(format!(
+                "unknown output format `{input}`"
+            ))),
+        }
+    }
+}
+
+/// Writes game reports as they come in, making sure that the
+/// whole output is valid JSON (or NDJSON).
+pub struct ReportWriter<W: Write> {
+    writer: W,
+    format: OutputFormat,
+    /// How many reports were written so far.
+    written: usize,
+}
+
+impl<W: Write> ReportWriter<W> {
+    pub fn new(writer: W, format: OutputFormat) -> Self {
+        Self {
+            writer,
+            format,
+            written: 0,
+        }
+    }
+
+    /// Writes a single report.
+    pub fn write(&mut self, report: &GameReport) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                self.write_json_entry(report)?
+            }
+            OutputFormat::Ndjson => {
+                self.writer.write_all(b"{")?;
+                serde_json::to_writer(
+                    &mut self.writer,
+                    &report.key(),
+                )?;
+                self.writer.write_all(b":")?;
+                serde_json::to_writer(&mut self.writer, report)?;
+                self.writer.write_all(b"}\n")?;
+            }
+        }
+
+        self.written += 1;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Terminates the output, returning the inner writer.
+    pub fn finish(mut self) -> Result<W> {
+        if self.format == OutputFormat::Json {
+            if self.written == 0 {
+                self.writer.write_all(b"{")?;
+            }
+            self.writer.write_all(b"\n}\n")?;
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_json_entry(
+        &mut self,
+        report: &GameReport,
+    ) -> Result<()> {
+        const INDENT: &[u8] = b"    ";
+
+        let separator: &[u8] =
+            if self.written == 0 { b"{\n" } else { b",\n" };
+        self.writer.write_all(separator)?;
+
+        self.writer.write_all(INDENT)?;
+        serde_json::to_writer(&mut self.writer, &report.key())?;
+        self.writer.write_all(b": ")?;
+
+        // Nest the pretty-printed report one level deeper. This
+        // is fine since serialized strings never contain
+        // raw newlines
+        let body = serde_json::to_vec_pretty(report)?;
+        for (idx, line) in
+            body.split(|&b| b == b'\n').enumerate()
+        {
+            if idx > 0 {
+                self.writer.write_all(b"\n")?;
+                self.writer.write_all(INDENT)?;
+            }
+            self.writer.write_all(line)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Cursor};
+
+    use super::{OutputFormat, ReportWriter};
+    use crate::{GameReport, LogParser};
+
+    const INPUT: &str = r#"
+    0:16 Kill: 6 2 7: Zeh killed Isgalamido by MOD_ROCKET_SPLASH
+    0:16 Kill: 2 7 6: Isgalamido killed Mal by MOD_ROCKET
+    0:17 Item: 4 weapon_rocketlauncher
+    0:18 Item: 5 item_armor_shard
+    0:18 Item: 5 item_armor_shard
+    0:18 Item: 5 item_armor_shard
+    0:18 Item: 5 item_armor_combat
+    0:21 Item: 6 weapon_rocketlauncher
+    0:23 Item: 2 weapon_rocketlauncher
+    0:26 Kill: 5 3 7: Assasinu Credi killed Oootsimo by MOD_ROCKET_SPLASH
+    0:27 Kill: 1022 4 19: <world> killed Dono da Bola by MOD_FALLING
+    0:28 Kill: 1022 7 22: <world> killed Mal by MOD_TRIGGER_HURT
+    0:31 Item: 7 weapon_rocketlauncher
+    0:31 Kill: 5 6 7: Assasinu Credi killed Zeh by MOD_ROCKET_SPLASH
+    0:32 Item: 5 weapon_rocketlauncher
+    0:33 ShutdownGame:"#;
+
+    fn parse_reports(input: &str) -> Vec<GameReport> {
+        let mut parser = LogParser::new();
+        let reader = BufReader::new(Cursor::new(input));
+
+        reader
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                parser.parse_line(&line.unwrap()).unwrap()
+            })
+            .collect()
+    }
+
+    fn write_reports(
+        reports: &[GameReport],
+        format: OutputFormat,
+    ) -> String {
+        let mut writer = ReportWriter::new(Vec::new(), format);
+
+        for report in reports {
+            writer.write(report).unwrap();
+        }
+
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn writes_json_reports() {
+        let reports = parse_reports(INPUT);
+
+        let gotten = write_reports(&reports, OutputFormat::Json);
+        let gotten: serde_json::Value =
+            serde_json::from_str(&gotten).unwrap();
+
+        let expected = serde_json::json!({
+            "game0": {
+                "total_kills": 6,
+                "players": ["Zeh", "Isgalamido", "Mal", "Assasinu Credi", "Oootsimo", "Dono da Bola"],
+                "kills": {
+                        "Mal": -1,
+                        "Isgalamido": 1,
+                        "Dono da Bola": -1,
+                        "Zeh": 1,
+                        "Oootsimo": 0,
+                        "Assasinu Credi": 2
+                },
+                "stats": {
+                        "Isgalamido": { "kills": 1, "deaths": 1, "world_deaths": 0, "self_kills": 0, "team_kills": 0, "kd_ratio": 1.0, "kills_by_means": { "MOD_ROCKET": 1 }, "deaths_by_means": { "MOD_ROCKET_SPLASH": 1 }, "kills_by_weapon": { "WP_ROCKET_LAUNCHER": 1 }, "deaths_by_category": { "weapon": 1 }, "items": {} },
+                        "Zeh": { "kills": 1, "deaths": 1, "world_deaths": 0, "self_kills": 0, "team_kills": 0, "kd_ratio": 1.0, "kills_by_means": { "MOD_ROCKET_SPLASH": 1 }, "deaths_by_means": { "MOD_ROCKET_SPLASH": 1 }, "kills_by_weapon": { "WP_ROCKET_LAUNCHER": 1 }, "deaths_by_category": { "weapon": 1 }, "items": {} },
+                        "Mal": { "kills": 0, "deaths": 2, "world_deaths": 1, "self_kills": 0, "team_kills": 0, "kd_ratio": 0.0, "kills_by_means": {}, "deaths_by_means": { "MOD_ROCKET": 1, "MOD_TRIGGER_HURT": 1 }, "kills_by_weapon": {}, "deaths_by_category": { "weapon": 1, "environment": 1 }, "items": {} },
+                        "Oootsimo": { "kills": 0, "deaths": 1, "world_deaths": 0, "self_kills": 0, "team_kills": 0, "kd_ratio": 0.0, "kills_by_means": {}, "deaths_by_means": { "MOD_ROCKET_SPLASH": 1 }, "kills_by_weapon": {}, "deaths_by_category": { "weapon": 1 }, "items": {} },
+                        "Assasinu Credi": { "kills": 2, "deaths": 0, "world_deaths": 0, "self_kills": 0, "team_kills": 0, "kd_ratio": 2.0, "kills_by_means": { "MOD_ROCKET_SPLASH": 2 }, "deaths_by_means": {}, "kills_by_weapon": { "WP_ROCKET_LAUNCHER": 2 }, "deaths_by_category": {}, "items": {} },
+                        "Dono da Bola": { "kills": 0, "deaths": 1, "world_deaths": 1, "self_kills": 0, "team_kills": 0, "kd_ratio": 0.0, "kills_by_means": {}, "deaths_by_means": { "MOD_FALLING": 1 }, "kills_by_weapon": {}, "deaths_by_category": { "environment": 1 }, "items": {} }
+                },
+                "kills_by_means": {
+                        "MOD_SHOTGUN": 0,
+                        "MOD_GAUNTLET": 0,
+                        "MOD_MACHINEGUN": 0,
+                        "MOD_GRENADE": 0,
+                        "MOD_GRENADE_SPLASH": 0,
+                        "MOD_ROCKET": 1,
+                        "MOD_ROCKET_SPLASH": 3,
+                        "MOD_PLASMA": 0,
+                        "MOD_PLASMA_SPLASH": 0,
+                        "MOD_RAILGUN": 0,
+                        "MOD_LIGHTNING": 0,
+                        "MOD_BFG": 0,
+                        "MOD_BFG_SPLASH": 0,
+                        "MOD_WATER": 0,
+                        "MOD_SLIME": 0,
+                        "MOD_LAVA": 0,
+                        "MOD_CRUSH": 0,
+                        "MOD_TELEFRAG": 0,
+                        "MOD_FALLING": 1,
+                        "MOD_SUICIDE": 0,
+                        "MOD_TARGET_LASER": 0,
+                        "MOD_TRIGGER_HURT": 1,
+                        "MOD_NAIL": 0,
+                        "MOD_CHAINGUN": 0,
+                        "MOD_PROXIMITY_MINE": 0,
+                        "MOD_KAMIKAZE": 0,
+                        "MOD_JUICED": 0,
+                        "MOD_GRAPPLE": 0,
+                        "MOD_UNKNOWN": 0
+                },
+                "kills_by_weapon": {
+                        "WP_GAUNTLET": 0,
+                        "WP_MACHINEGUN": 0,
+                        "WP_SHOTGUN": 0,
+                        "WP_GRENADE_LAUNCHER": 0,
+                        "WP_ROCKET_LAUNCHER": 4,
+                        "WP_LIGHTNING_GUN": 0,
+                        "WP_RAILGUN": 0,
+                        "WP_PLASMA_GUN": 0,
+                        "WP_BFG": 0,
+                        "WP_GRAPPLING_HOOK": 0,
+                        "WP_NAILGUN": 0,
+                        "WP_PROX_LAUNCHER": 0,
+                        "WP_CHAINGUN": 0
+                },
+                "deaths_by_category": {
+                        "weapon": 4,
+                        "environment": 2,
+                        "self": 0,
+                        "telefrag": 0,
+                        "team_arena": 0,
+                        "unknown": 0
+                },
+                "items": {
+                        "item_armor_shard": 3,
+                        "item_armor_combat": 1,
+                        "weapon_rocketlauncher": 5
+                },
+                "scoreboard": [],
+                "settings": null,
+                "duration": null,
+                "incomplete": false,
+                "source": null
+            }
+        });
+
+        assert_eq!(gotten, expected);
+    }
+
+    #[test]
+    fn escapes_player_names() {
+        let reports = parse_reports(concat!(
+            "\n",
+            r#"  0:01 Kill: 2 3 7: "Bob\ killed Mal by MOD_ROCKET"#,
+            "\n",
+            "  0:02 ShutdownGame:",
+        ));
+
+        for format in [OutputFormat::Json, OutputFormat::Ndjson]
+        {
+            let output = write_reports(&reports, format);
+
+            for document in
+                serde_json::Deserializer::from_str(&output)
+                    .into_iter::<serde_json::Value>()
+            {
+                let document = document.unwrap();
+                assert_eq!(
+                    document["game0"]["kills"][r#""Bob\"#],
+                    1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn writes_one_line_per_game_as_ndjson() {
+        let reports = parse_reports(
+            "\n  0:01 ShutdownGame:\n  0:02 ShutdownGame:",
+        );
+
+        let output =
+            write_reports(&reports, OutputFormat::Ndjson);
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"game0":"#));
+        assert!(lines[1].starts_with(r#"{"game1":"#));
+    }
+
+    #[test]
+    fn writes_empty_json_object_without_games() {
+        let output = write_reports(&[], OutputFormat::Json);
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&output)
+                .unwrap(),
+            serde_json::json!({})
+        );
+    }
+}
@@ -1,22 +1,31 @@
 mod combinator;
-mod display;
+mod event;
+mod handler;
 mod header;
+mod players;
+mod score_keeper;
 
-use std::{collections::HashMap, rc::Rc};
-
-use nom::{Finish, IResult};
+use std::time::Duration;
 
 use self::{
-    combinator::parse_kill,
-    header::{parse_header, Header},
+    event::{parse_event, EventError, GameClock},
+    handler::Handlers,
+};
+pub use self::{
+    event::{Event, Events, Kill, TimedEvent},
+    handler::{EventHandler, ResolvedKill},
 };
 use crate::{
-    ensure, extra_checked_ops::ExtraCheckedOps,
-    instance_counter::InstanceCounter, CauseOfDeath, Error,
-    Result, CAUSES_OF_DEATH,
+    instance_counter::InstanceCounter, strip_color_codes,
+    CauseOfDeath, ClientId, ClientInfo, Diagnostic, Error,
+    GameReport, GameSettings, LogLine, ParseMode, PlayerOrder,
+    PlayerRegistry, Result, ScoringRules, Team, Warning,
+    CAUSES_OF_DEATH,
 };
 
 const WORLD: &str = "<world>";
+/// The client id used for `<world>` in kill lines.
+const WORLD_ID: ClientId = 1022;
 
 pub type CauseOfDeathCounter =
     InstanceCounter<CauseOfDeath, CAUSES_OF_DEATH>;
@@ -24,183 +33,1299 @@ pub type CauseOfDeathCounter =
 /// A parser for Quake 3 Arena logs
 pub struct LogParser {
     /// The index of the current game.
-    game_idx: u8,
-    /// How many kills happened during this game,
-    /// including the ones caused by `<world>`.
-    total_kills: u32,
-    /// Totals up how many deaths were caused by each
-    /// cause of death.
-    cause_of_death_counter: CauseOfDeathCounter,
-    /// Maps each player to his score.
-    scores: HashMap<Rc<str>, i32>,
+    game_idx: u32,
+    /// Collect the statistics of every game, starting with the
+    /// players' scores.
+    handlers: Handlers,
+    /// The clients currently connected to the server.
+    registry: PlayerRegistry,
+    /// The server settings of the current game.
+    settings: Option<GameSettings>,
+    /// What to do with lines that can't be parsed.
+    mode: ParseMode,
+    /// Warnings raised since they were last taken.
+    warnings: Vec<Warning>,
+    /// The lines skipped for being malformed, in lenient mode.
+    diagnostics: Vec<Diagnostic>,
+    /// The name of the log being parsed, if given.
+    source: Option<String>,
+    /// How many lines of this log were parsed so far.
+    lines_read: usize,
+    /// How many bytes of this log were parsed so far.
+    bytes_read: usize,
+    /// Whether a game was started, or had anything happen in
+    /// it, without having ended yet.
+    game_open: bool,
+    /// The log the current game started in.
+    game_source: Option<String>,
+    /// Times events against the start of their game.
+    clock: GameClock,
+    /// How long the current game has been going on for, as of
+    /// its latest event.
+    game_duration: Option<Duration>,
 }
 
 impl LogParser {
-    /// Build a new, empty `LogpParser`
+    /// Build a new, empty `LogParser`
     pub fn new() -> Self {
         Self {
-            cause_of_death_counter: InstanceCounter::new(),
-            scores: HashMap::new(),
+            handlers: Handlers::default(),
+            registry: PlayerRegistry::default(),
+            settings: None,
+            mode: ParseMode::default(),
+            warnings: Vec::new(),
+            diagnostics: Vec::new(),
+            lines_read: 0,
+            bytes_read: 0,
             game_idx: 0,
-            total_kills: 0,
+            game_open: false,
+            source: None,
+            game_source: None,
+            clock: GameClock::default(),
+            game_duration: None,
         }
     }
 
+    /// Sets the order in which players are listed in reports.
+    ///
+    /// Players are listed in order of first appearance by
+    /// default.
+    pub fn with_player_order(
+        mut self,
+        order: PlayerOrder,
+    ) -> Self {
+        self.handlers.score_keeper.player_order = order;
+        self
+    }
+
+    /// Sets how kills affect the players' scores.
+    ///
+    /// The game's own rules are followed by default.
+    pub fn with_scoring_rules(
+        mut self,
+        rules: ScoringRules,
+    ) -> Self {
+        self.handlers.score_keeper.scoring_rules = rules;
+        self
+    }
+
+    /// Registers a handler to be told about every event of the
+    /// log, such as to collect statistics of its own.
+    ///
+    /// Handlers are called in order of registration, after the
+    /// built-in one that keeps the scores of reports.
+    pub fn with_handler(
+        mut self,
+        handler: impl EventHandler + 'static,
+    ) -> Self {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Sets what to do with lines that can't be parsed.
+    ///
+    /// Parsing is strict by default.
+    pub fn with_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the name of the log whose lines are about to be
+    /// parsed, such as its path.
+    ///
+    /// Reports and diagnostics are tagged with it, and line
+    /// numbers start over from this point. Games carry on from
+    /// one log to the next, so that several rotated logs can be
+    /// parsed in order as if they were a single one.
+    pub fn set_source(&mut self, source: impl Into<String>) {
+        self.source = Some(source.into());
+        self.lines_read = 0;
+        self.bytes_read = 0;
+    }
+
     /// Parses a single line of a Quake 3 Arena log.
     ///
-    /// If a game has ended, this function will print
-    /// a report of the game to stdout.
-    pub fn parse_line(&mut self, input: &str) -> Result<()> {
-        let (rest, action) =
-            parse_header(input).map_err(Self::convert_error)?;
+    /// If this line ended a game, a report of that game is
+    /// returned. A game also ends when another one starts before
+    /// it was shut down, in which case its report is marked as
+    /// incomplete.
+    ///
+    /// If the line ended a game but then failed, the report of
+    /// that game is returned along with the error, as an
+    /// [`Error::GameEnded`].
+    ///
+    /// Lines are expected to be given in order, along with their
+    /// line terminators, so that diagnostics point to the right
+    /// spot of the log. Lines read through
+    /// [`ReallocBufReader`](crate::ReallocBufReader) carry their
+    /// length in the log, which keeps offsets right even for
+    /// lines decoded from Latin-1.
+    pub fn parse_line<'a>(
+        &mut self,
+        input: impl Into<LogLine<'a>>,
+    ) -> Result<Option<GameReport>> {
+        let line = input.into();
+        let result = self.handle_line(line);
+        let offset = self.bytes_read;
+        self.lines_read += 1;
+        self.bytes_read += line.raw_len;
+
+        // Errors raised once the line was parsed, such as by
+        // handlers, leave it half-applied rather than skipped
+        let skipped = match &result {
+            Err(Error::ParsingError(err)) => Some((
+                err.reason(),
+                Some(err.column),
+                Some(err.char_column),
+            )),
+            Err(Error::InvalidLine(err)) => {
+                Some((err.error.to_string(), None, None))
+            }
+            _ => None,
+        };
+
+        match skipped {
+            Some((reason, column, char_column))
+                if self.mode == ParseMode::Lenient =>
+            {
+                self.diagnostics.push(Diagnostic {
+                    source: self.source.clone(),
+                    line: self.lines_read,
+                    offset,
+                    column,
+                    char_column,
+                    reason,
+                    raw_line: trim_line_terminator(line.text)
+                        .into(),
+                });
+
+                Ok(None)
+            }
+            _ => result,
+        }
+    }
+
+    fn handle_line(
+        &mut self,
+        line: LogLine,
+    ) -> Result<Option<GameReport>> {
+        let mut event = parse_event(line.text)
+            .map_err(|err| self.convert_error(line, err))?;
+        self.clock.stamp(&mut event);
+
+        let mut report = None;
+        match &event.event {
+            Event::InitGame(settings) => {
+                // The server went down (or restarted) before
+                // ending the previous game
+                report = self.finish();
+
+                self.settings = Some(settings.clone());
+                self.open_game();
+            }
+            Event::Kill(_)
+            | Event::ClientUserinfoChanged { .. } => {
+                self.open_game();
+            }
+            _ => {}
+        }
+
+        // Timestamps start over when the server restarts, even
+        // in the middle of a game
+        if self.game_open {
+            self.game_duration =
+                self.game_duration.max(event.game_time);
+        }
+
+        // The previous game is over whatever happens next, so
+        // its report goes along with any error
+        match self.dispatch(event) {
+            Ok(ended) => Ok(ended.or(report)),
+            Err(error) => Err(match report {
+                Some(report) => Error::GameEnded {
+                    report: Box::new(report),
+                    error: Box::new(error),
+                },
+                None => error,
+            }),
+        }
+    }
+
+    /// Hands an event over to the handlers, then updates the
+    /// state of the game with it, returning the report of the
+    /// game it shut down, if any.
+    fn dispatch(
+        &mut self,
+        event: TimedEvent,
+    ) -> Result<Option<GameReport>> {
+        for handler in self.handlers.iter_mut() {
+            handler.event(&event, &self.registry)?;
+        }
 
-        match action {
-            Header::Kill => {
-                self.handle_kill(rest)?;
+        match event.event {
+            Event::Kill(kill) => {
+                self.handle_kill(
+                    &kill,
+                    event.timestamp,
+                    event.game_time,
+                )?;
+            }
+            Event::ClientUserinfoChanged { client_id, info } => {
+                self.handle_userinfo_changed(client_id, info)?;
+            }
+            Event::ClientDisconnect { client_id } => {
+                self.registry.remove(client_id);
+            }
+            Event::ShutdownGame => {
+                return Ok(Some(self.handle_shutdown(false)))
             }
-            Header::ShutdownGame => self.handle_shutdown()?,
             _ => {
                 // Not relevant for this application
             }
         }
 
-        Ok(())
+        Ok(None)
+    }
+
+    /// Ends the game in progress, if any, returning its report
+    /// marked as incomplete.
+    ///
+    /// Meant to be called once the log is over, since its last
+    /// game may not have been shut down.
+    pub fn finish(&mut self) -> Option<GameReport> {
+        if !self.game_open {
+            return None;
+        }
+
+        Some(self.handle_shutdown(true))
     }
 
-    /// Insert the given username into the parser's
-    /// player buffer or return it if already inserted.
-    fn intern_username(&mut self, username: &str) -> Rc<str> {
-        self.scores
-            .get_key_value(username)
-            .map(|(cached_username, _)| cached_username.clone())
-            .unwrap_or_else(|| Rc::from(username))
+    /// The clients connected to the server during the
+    /// current game.
+    pub fn registry(&self) -> &PlayerRegistry {
+        &self.registry
     }
 
-    /// Converts a `nom` Error into a `crate::Error`
+    /// Takes the warnings raised since the last call to this
+    /// function.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// The lines skipped so far for being malformed, leaving out
+    /// the ones already taken through
+    /// [`take_diagnostics`](Self::take_diagnostics).
+    ///
+    /// Always empty in strict mode.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Takes the lines skipped since the last call to this
+    /// function, so that they don't pile up when parsing a log
+    /// that never ends.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Converts an error raised while parsing `line` into a
+    /// `crate::Error` pointing at where the line failed to parse
     fn convert_error(
-        error: nom::Err<nom::error::Error<&str>>,
+        &self,
+        line: LogLine,
+        error: EventError,
     ) -> Error {
-        let result: IResult<_, &str> = Err(error.to_owned());
-        let result = result.finish();
+        error.locate(
+            line,
+            self.source.as_deref(),
+            self.lines_read,
+            self.bytes_read,
+        )
+    }
+
+    /// Marks the current game as started, if it wasn't yet.
+    fn open_game(&mut self) {
+        if !self.game_open {
+            self.game_open = true;
+            self.game_source = self.source.clone();
 
-        // This result is guaranteed to be an error,
-        // so unwrap_err will never fail
-        Error::ParsingError(result.unwrap_err())
+            for handler in self.handlers.iter_mut() {
+                handler.game_start(self.settings.as_ref());
+            }
+        }
     }
 
-    fn handle_shutdown(&mut self) -> Result<()> {
-        println!("{self}");
+    fn handle_shutdown(
+        &mut self,
+        incomplete: bool,
+    ) -> GameReport {
+        let mut report = GameReport {
+            game_idx: self.game_idx,
+            settings: self.settings.clone(),
+            duration: self.game_duration,
+            incomplete,
+            // Games shut down before anything happened in them
+            // belong to the log they were shut down in
+            source: self
+                .game_source
+                .clone()
+                .or_else(|| self.source.clone()),
+            ..GameReport::default()
+        };
 
+        for handler in self.handlers.iter_mut() {
+            handler.game_end(&mut report);
+        }
+
+        self.reconcile_scores(&report);
         self.clear();
-        Ok(())
+        report
+    }
+
+    /// Cross-checks the scores computed for a game with the
+    /// final ones logged by the server, if any.
+    fn reconcile_scores(&mut self, report: &GameReport) {
+        // Custom rules are bound to disagree with the server's,
+        // and so are game modes that score objectives
+        let scores_by_kills =
+            report.settings.as_ref().is_none_or(|settings| {
+                settings.game_type.scores_by_kills()
+            });
+        if !scores_by_kills
+            || self.handlers.score_keeper.scoring_rules
+                != ScoringRules::default()
+        {
+            return;
+        }
+
+        for entry in &report.scoreboard {
+            // Players who never killed nor died have yet to
+            // score
+            let computed =
+                report.score_of(&entry.name).unwrap_or(0);
+
+            if computed != entry.score {
+                self.warnings.push(Warning::ScoreMismatch {
+                    game: report.key(),
+                    player: entry.name.clone(),
+                    computed,
+                    logged: entry.score,
+                });
+            }
+        }
     }
 
     fn clear(&mut self) {
         // Get the game counter ready for the next game ..
         self.game_idx += 1;
 
-        // .. and then reset all the rest
-        self.scores.clear();
-        self.total_kills = 0;
-        self.cause_of_death_counter = InstanceCounter::new();
+        // .. and then reset all the rest, which handlers do on
+        // their own once the game ends
+        self.registry.clear();
+        self.settings = None;
+        self.game_open = false;
+        self.game_source = None;
+        self.game_duration = None;
     }
 
-    fn handle_kill(&mut self, input: &str) -> Result<()> {
-        let (rest, message) =
-            parse_kill(input).map_err(Self::convert_error)?;
+    fn handle_kill(
+        &mut self,
+        kill: &Kill,
+        timestamp: Duration,
+        game_time: Option<Duration>,
+    ) -> Result<()> {
+        let attacker =
+            self.resolve_name(kill.attacker_id, &kill.attacker);
+        let victim =
+            self.resolve_name(kill.victim_id, &kill.victim);
 
-        ensure!(
-            rest.trim().is_empty(),
-            "Line contained unexpected input"
+        // Cross-check the logged names with the ones given by
+        // the client ids
+        let logged = kill.message();
+        let expected = format!(
+            "{attacker} killed {victim} by {}",
+            kill.cause_of_death
         );
+        if strip_color_codes(&logged) != expected {
+            self.warnings.push(Warning::KillMismatch {
+                logged,
+                expected,
+            });
+        }
 
-        self.cause_of_death_counter
-            .add(message.cause_of_death)?;
+        let kill = ResolvedKill {
+            kill,
+            timestamp,
+            game_time,
+            team_kill: kill.attacker_id != kill.victim_id
+                && self.are_teammates(
+                    kill.attacker_id,
+                    kill.victim_id,
+                ),
+            attacker: &attacker,
+            victim: &victim,
+        };
 
-        let victim = self.intern_username(message.victim);
+        for handler in self.handlers.iter_mut() {
+            handler.kill(&kill)?;
+        }
 
-        if message.attacker == WORLD {
-            // Victim must get discounted one point
-            self.scores
-                .entry(victim)
-                .or_default()
-                .checked_decrement()?;
-        } else {
-            let attacker =
-                self.intern_username(message.attacker);
+        Ok(())
+    }
+
+    /// Whether both clients play on the same team, in team-based
+    /// game modes.
+    fn are_teammates(
+        &self,
+        first: ClientId,
+        second: ClientId,
+    ) -> bool {
+        let team_of = |client_id| {
+            self.registry
+                .get(client_id)
+                .map(|client| client.team)
+        };
 
-            self.scores
-                .entry(attacker)
-                .or_default()
-                .checked_increment()?;
+        match (team_of(first), team_of(second)) {
+            (Some(first), Some(second)) => {
+                first == second
+                    && matches!(first, Team::Red | Team::Blue)
+            }
+            _ => false,
         }
+    }
 
-        self.total_kills.checked_increment()?;
+    /// Finds out the name of the given client, falling back to
+    /// the name logged alongside its id if it's not registered.
+    ///
+    /// Names are stripped of their color codes either way.
+    fn resolve_name(
+        &self,
+        client_id: ClientId,
+        logged_name: &str,
+    ) -> String {
+        if client_id == WORLD_ID {
+            return WORLD.into();
+        }
+
+        match self.registry.get(client_id) {
+            Some(client) if !client.name.is_empty() => {
+                client.name.clone()
+            }
+            _ => strip_color_codes(logged_name),
+        }
+    }
+
+    fn handle_userinfo_changed(
+        &mut self,
+        client_id: ClientId,
+        info: ClientInfo,
+    ) -> Result<()> {
+        let previous = self.registry.insert(client_id, info);
+        let info = self
+            .registry
+            .get(client_id)
+            .expect("the client was just registered");
+
+        for handler in self.handlers.iter_mut() {
+            handler.userinfo_changed(
+                client_id,
+                info,
+                previous.as_ref(),
+            )?;
+        }
 
         Ok(())
     }
 }
 
+/// Strips the `\n` or `\r\n` a line ends with, if any.
+fn trim_line_terminator(line: &str) -> &str {
+    line.trim_end_matches(['\r', '\n'])
+}
+
+impl Default for LogParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
-
     use super::LogParser;
-    use crate::CauseOfDeath;
+    use crate::{
+        CauseOfDeath, Diagnostic, Error, Event, EventHandler,
+        GameReport, GameSettings, Item, ParseMode,
+        PlayerRegistry, ResolvedKill, Result, ScoreboardEntry,
+        ScoringRules, Team, TimedEvent, Warning,
+    };
 
     #[test]
     fn parser_saves_players_correctly() {
         let mut parser = LogParser::new();
-        let snek: Rc<str> = Rc::from("snek");
-        let crab: Rc<str> = Rc::from("crab");
-        let gopher: Rc<str> = Rc::from("gopher");
+        let snek = "snek";
+        let crab = "crab";
+        let gopher = "gopher";
 
-        parser.parse_line(" 21:42 Kill: 1022 2 22: crab killed gopher by MOD_ROCKET").unwrap();
-        parser.parse_line(" 21:42 Kill: 1022 2 22: crab killed gopher by MOD_ROCKET").unwrap();
-        parser.parse_line(" 21:43 Kill: 1022 2 22: crab killed snek by MOD_ROCKET").unwrap();
+        parser.parse_line(" 21:42 Kill: 3 2 6: crab killed gopher by MOD_ROCKET").unwrap();
+        parser.parse_line(" 21:42 Kill: 3 2 6: crab killed gopher by MOD_ROCKET").unwrap();
+        parser.parse_line(" 21:43 Kill: 3 4 6: crab killed snek by MOD_ROCKET").unwrap();
         parser.parse_line(" 21:43 Kill: 1022 2 22: <world> killed gopher by MOD_LAVA").unwrap();
 
         assert_eq!(
             parser
+                .handlers
+                .score_keeper
                 .cause_of_death_counter
                 .get(CauseOfDeath::Rocket)
                 .unwrap(),
             3
         );
 
-        // assert_eq!(
-        //     HashSet::from_iter(parser.players.into_iter()),
-        //     HashSet::from([
-        //         snek.clone(),
-        //         crab.clone(),
-        //         gopher.clone()
-        //     ])
-        // );
+        let players: Vec<_> = parser
+            .handlers
+            .score_keeper
+            .players
+            .iter()
+            .map(|player| &*player.name)
+            .collect();
+        assert_eq!(players, [crab, gopher, snek]);
+
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get(crab)
+                .unwrap()
+                .score,
+            3
+        );
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get(gopher)
+                .unwrap()
+                .score,
+            -1
+        );
+
+        // `snek` didn't score but still gets reported
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get(snek)
+                .unwrap()
+                .score,
+            0
+        );
+    }
 
-        assert_eq!(*parser.scores.get(&crab).unwrap(), 3);
-        assert_eq!(*parser.scores.get(&gopher).unwrap(), -1);
+    #[test]
+    fn parser_saves_players_from_userinfo() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(r" 20:34 ClientUserinfoChanged: 2 n\Isgalamido\t\0\model\xian/default\hmodel\xian/default").unwrap();
 
-        // `snek` didn't score so it did not get included in the
-        // map. This is fixed in `LogParser::handle_shutdown`
-        // before printing the report.
-        assert_eq!(parser.scores.get(&snek), None);
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Isgalamido")
+                .unwrap()
+                .score,
+            0
+        );
+    }
+
+    #[test]
+    fn parser_keeps_clients_with_invalid_numbers() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(r" 20:34 ClientUserinfoChanged: 2 n\Zeh\t\5\model\sarge\hc\").unwrap();
+        parser
+            .parse_line(" 20:35 Item: 2 weapon_railgun")
+            .unwrap();
+
+        let zeh = parser.registry.get(2).unwrap();
+        assert_eq!(zeh.name, "Zeh");
+        assert_eq!(zeh.handicap, 100);
+
+        let report = parser
+            .parse_line(" 20:36 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            report
+                .stats_of("Zeh")
+                .unwrap()
+                .items
+                .get(Item::Railgun),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn parser_follows_name_changes() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(r" 20:34 ClientUserinfoChanged: 2 n\Zeh\t\0\model\sarge").unwrap();
+        parser.parse_line(" 20:35 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(r" 20:36 ClientUserinfoChanged: 2 n\Zehzinho\t\1\model\sarge").unwrap();
+
+        assert!(parser
+            .handlers
+            .score_keeper
+            .players
+            .get("Zeh")
+            .is_none());
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Zehzinho")
+                .unwrap()
+                .score,
+            1
+        );
+
+        let client = parser.registry().get(2).unwrap();
+        assert_eq!(client.name, "Zehzinho");
+        assert_eq!(client.team, Team::Red);
+
+        parser.parse_line(" 20:37 ClientDisconnect: 2").unwrap();
+        assert!(parser.registry().get(2).is_none());
+    }
+
+    #[test]
+    fn parser_attributes_kills_through_client_ids() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(r" 0:01 ClientUserinfoChanged: 2 n\Bob killed Al\t\0").unwrap();
+        parser
+            .parse_line(
+                r" 0:01 ClientUserinfoChanged: 3 n\Ann\t\0",
+            )
+            .unwrap();
+
+        // Ambiguous names get resolved through the client ids
+        parser.parse_line(" 0:02 Kill: 2 3 10: Bob killed Al killed Ann by MOD_RAILGUN").unwrap();
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Bob killed Al")
+                .unwrap()
+                .score,
+            1
+        );
+        assert!(parser.take_warnings().is_empty());
+
+        // The ids win over mismatching names, with a warning
+        parser.parse_line(" 0:03 Kill: 3 2 10: Mal killed Bob killed Al by MOD_RAILGUN").unwrap();
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Ann")
+                .unwrap()
+                .score,
+            1
+        );
+        assert!(parser
+            .handlers
+            .score_keeper
+            .players
+            .get("Mal")
+            .is_none());
+        assert_eq!(
+            parser.take_warnings(),
+            [Warning::KillMismatch {
+                logged:
+                    "Mal killed Bob killed Al by MOD_RAILGUN"
+                        .into(),
+                expected:
+                    "Ann killed Bob killed Al by MOD_RAILGUN"
+                        .into(),
+            }]
+        );
     }
 
     #[test]
     fn parser_saves_players_correctly_when_killed_by_world() {
         let mut parser = LogParser::new();
-        let player: Rc<str> = Rc::from("xXplayerXx");
+        let player = "xXplayerXx";
 
         parser.parse_line(" 21:42 Kill: 1022 2 22: <world> killed xXplayerXx by MOD_TRIGGER_HURT").unwrap();
 
         assert_eq!(
             parser
+                .handlers
+                .score_keeper
                 .cause_of_death_counter
                 .get(CauseOfDeath::TriggerHurt)
                 .unwrap(),
             1
         );
-        // assert_eq!(parser.players, &[player.clone()]);
-        assert_eq!(*parser.scores.get(&player).unwrap(), -1);
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get(player)
+                .unwrap()
+                .score,
+            -1
+        );
+    }
+
+    #[test]
+    fn lenient_parser_skips_malformed_lines() {
+        let mut parser =
+            LogParser::new().with_mode(ParseMode::Lenient);
+
+        let lines = [
+            "  0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET\n",
+            "  0:02 Banana: 2\n",
+            "  0:03 Kill: 2 3 7: Zeh killed Mal by MOD_BANANA\r\n",
+            "  0:04 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET\n",
+        ];
+
+        for line in lines {
+            assert_eq!(parser.parse_line(line).unwrap(), None);
+        }
+
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Zeh")
+                .unwrap()
+                .score,
+            2
+        );
+
+        let diagnostics = parser.diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].line, 2);
+        assert_eq!(diagnostics[0].offset, lines[0].len());
+        assert_eq!(diagnostics[0].raw_line, "  0:02 Banana: 2");
+
+        assert_eq!(
+            diagnostics[1],
+            Diagnostic {
+                source: None,
+                line: 3,
+                offset: lines[0].len() + lines[1].len(),
+                column: Some(
+                    lines[2].find("MOD_BANANA").unwrap() + 1
+                ),
+                char_column: Some(
+                    lines[2].find("MOD_BANANA").unwrap() + 1
+                ),
+                reason: diagnostics[1].reason.clone(),
+                raw_line: lines[2].trim_end().into(),
+            }
+        );
+
+        assert_eq!(parser.take_diagnostics().len(), 2);
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_offending_spot() {
+        let mut parser = LogParser::new();
+
+        parser
+            .parse_line("  0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET\n")
+            .unwrap();
+
+        let line =
+            "  0:02 Kill: 2 3 7: Zeh killed Mal by MOD_BANANA\n";
+        let Err(Error::ParsingError(error)) =
+            parser.parse_line(line)
+        else {
+            panic!("expected a parsing error");
+        };
+
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 39);
+        assert_eq!(error.offset, 49 + 38);
+        assert_eq!(error.line_text, line.trim_end());
+        assert_eq!(
+            error.to_string(),
+            concat!(
+                "line 2, column 39 (byte 87): unexpected input (Tag)\n",
+                "  |\n",
+                "2 |   0:02 Kill: 2 3 7: Zeh killed Mal by MOD_BANANA\n",
+                "  |                                       ^",
+            )
+        );
+
+        // Trailing garbage is pointed at as well
+        let Err(Error::ParsingError(error)) = parser.parse_line(
+            "  0:03 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET banana\n",
+        ) else {
+            panic!("expected a parsing error");
+        };
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 50);
+    }
+
+    #[test]
+    fn parser_ignores_color_codes() {
+        let mut parser = LogParser::new();
+
+        parser
+            .parse_line(
+                r" 0:01 ClientUserinfoChanged: 2 n\^1Zeh\t\0",
+            )
+            .unwrap();
+        parser.parse_line(" 0:02 Kill: 2 3 7: ^1Zeh killed ^4Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:03 Kill: 3 2 7: Mal killed Zeh by MOD_ROCKET").unwrap();
+
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Zeh")
+                .unwrap()
+                .score,
+            1
+        );
+        assert_eq!(
+            parser
+                .handlers
+                .score_keeper
+                .players
+                .get("Mal")
+                .unwrap()
+                .score,
+            1
+        );
+        assert!(parser.take_warnings().is_empty());
+
+        let client = parser.registry().get(2).unwrap();
+        assert_eq!(client.raw_name, "^1Zeh");
+    }
+
+    #[test]
+    fn parser_breaks_down_kills_and_deaths() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(" 0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:02 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:03 Kill: 1022 2 19: <world> killed Zeh by MOD_FALLING").unwrap();
+        parser.parse_line(" 0:04 Kill: 3 3 7: Mal killed Mal by MOD_ROCKET_SPLASH").unwrap();
+
+        let report = parser
+            .parse_line(" 0:05 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        let zeh = report.stats_of("Zeh").unwrap();
+        assert_eq!(zeh.kills, 2);
+        assert_eq!(zeh.deaths, 1);
+        assert_eq!(zeh.world_deaths, 1);
+        assert_eq!(zeh.self_kills, 0);
+        assert_eq!(zeh.kd_ratio(), 2.0);
+        assert_eq!(
+            zeh.kills_by_means.get(CauseOfDeath::Rocket),
+            Some(2)
+        );
+        assert_eq!(
+            zeh.deaths_by_means.get(CauseOfDeath::Falling),
+            Some(1)
+        );
+
+        // Killing oneself is no kill
+        let mal = report.stats_of("Mal").unwrap();
+        assert_eq!(mal.kills, 0);
+        assert_eq!(mal.deaths, 3);
+        assert_eq!(mal.self_kills, 1);
+        assert_eq!(mal.kd_ratio(), 0.0);
+        assert_eq!(mal.kills_by_means.total().unwrap(), 0);
+        assert_eq!(
+            mal.deaths_by_means.get(CauseOfDeath::Rocket),
+            Some(2)
+        );
+        assert_eq!(
+            mal.deaths_by_means.get(CauseOfDeath::RocketSplash),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn parser_counts_item_pickups() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(r" 0:01 ClientUserinfoChanged: 2 n\Zeh\t\0\model\sarge").unwrap();
+        parser
+            .parse_line(" 0:02 Item: 2 weapon_railgun")
+            .unwrap();
+        parser.parse_line(" 0:03 Item: 2 ammo_slugs").unwrap();
+        parser.parse_line(" 0:04 Item: 2 ammo_slugs").unwrap();
+        // Nobody knows who client 3 is
+        parser.parse_line(" 0:05 Item: 3 ammo_slugs").unwrap();
+        // Items added by mods are counted apart
+        parser.parse_line(" 0:05 Item: 2 weapon_hmg").unwrap();
+        parser.parse_line(" 0:05 Item: 3 weapon_hmg").unwrap();
+
+        let report = parser
+            .parse_line(" 0:06 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        let zeh = report.stats_of("Zeh").unwrap();
+        assert_eq!(zeh.items.get(Item::Railgun), Some(1));
+        assert_eq!(zeh.items.get(Item::Slugs), Some(2));
+        assert_eq!(zeh.items.get(Item::Quad), Some(0));
+
+        assert_eq!(report.items.get(Item::Railgun), Some(1));
+        assert_eq!(report.items.get(Item::Slugs), Some(3));
+        assert_eq!(report.items.total().unwrap(), 4);
+        assert_eq!(zeh.other_items["weapon_hmg"], 1);
+        assert_eq!(report.other_items["weapon_hmg"], 2);
+        assert_eq!(report.players, ["Zeh"]);
+    }
+
+    #[test]
+    fn parser_reconciles_scores_with_the_scoreboard() {
+        let mut parser = LogParser::new();
+
+        parser
+            .parse_line(
+                r" 0:01 ClientUserinfoChanged: 2 n\^1Zeh\t\0",
+            )
+            .unwrap();
+        parser
+            .parse_line(
+                r" 0:01 ClientUserinfoChanged: 3 n\Mal\t\0",
+            )
+            .unwrap();
+        parser.parse_line(" 0:02 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:03 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:04 Exit: Fraglimit hit.").unwrap();
+        parser
+            .parse_line(
+                " 0:04 score: 2  ping: 4  client: 2 ^1Zeh",
+            )
+            .unwrap();
+        parser
+            .parse_line(" 0:04 score: 1  ping: 9  client: 3 Mal")
+            .unwrap();
+
+        let report = parser
+            .parse_line(" 0:05 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            report.scoreboard,
+            [
+                ScoreboardEntry {
+                    score: 2,
+                    ping: 4,
+                    client_id: 2,
+                    name: "Zeh".into(),
+                },
+                ScoreboardEntry {
+                    score: 1,
+                    ping: 9,
+                    client_id: 3,
+                    name: "Mal".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            parser.take_warnings(),
+            [Warning::ScoreMismatch {
+                game: "game0".into(),
+                player: "Mal".into(),
+                computed: 0,
+                logged: 1,
+            }]
+        );
+
+        // Flag captures are worth points in CTF games
+        parser
+            .parse_line(
+                r" 0:00 InitGame: \g_gametype\4\mapname\q3ctf1",
+            )
+            .unwrap();
+        parser
+            .parse_line(
+                r" 0:01 ClientUserinfoChanged: 2 n\Zeh\t\1",
+            )
+            .unwrap();
+        parser
+            .parse_line(" 0:02 score: 5  ping: 4  client: 2 Zeh")
+            .unwrap();
+
+        let report = parser
+            .parse_line(" 0:03 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.scoreboard.len(), 1);
+        assert!(parser.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn parser_follows_the_games_scoring_rules() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(" 0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:02 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:03 Kill: 1022 2 19: <world> killed Zeh by MOD_FALLING").unwrap();
+        parser.parse_line(" 0:04 Kill: 3 3 7: Mal killed Mal by MOD_ROCKET_SPLASH").unwrap();
+
+        let report = parser
+            .parse_line(" 0:05 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        // Killing oneself costs a point, like dying to <world>
+        assert_eq!(report.score_of("Zeh"), Some(1));
+        assert_eq!(report.score_of("Mal"), Some(-1));
+    }
+
+    #[test]
+    fn parser_tells_players_apart_by_client_id() {
+        let mut parser = LogParser::new();
+
+        parser.parse_line(r" 0:01 ClientUserinfoChanged: 2 n\UnnamedPlayer\t\0").unwrap();
+        parser.parse_line(r" 0:01 ClientUserinfoChanged: 3 n\UnnamedPlayer\t\0").unwrap();
+        parser
+            .parse_line(
+                r" 0:01 ClientUserinfoChanged: 4 n\<world>\t\0",
+            )
+            .unwrap();
+        parser.parse_line(" 0:02 Kill: 2 3 7: UnnamedPlayer killed UnnamedPlayer by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:03 Kill: 4 2 7: <world> killed UnnamedPlayer by MOD_ROCKET").unwrap();
+
+        let report = parser
+            .parse_line(" 0:04 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        // A frag between namesakes, and one by a player who
+        // named themselves after the map
+        let unnamed = report.stats_of("UnnamedPlayer").unwrap();
+        assert_eq!(unnamed.kills, 1);
+        assert_eq!(unnamed.self_kills, 0);
+        assert_eq!(unnamed.world_deaths, 0);
+        assert_eq!(report.stats_of("<world>").unwrap().kills, 1);
+        assert_eq!(report.score_of("UnnamedPlayer"), Some(1));
+        assert_eq!(report.score_of("<world>"), Some(1));
+    }
+
+    #[test]
+    fn parser_follows_scoring_rules() {
+        let rules = ScoringRules {
+            kill_points: 2,
+            world_penalty: 0,
+            suicide_penalty: 3,
+            team_kill_penalty: 5,
+        };
+        let mut parser =
+            LogParser::new().with_scoring_rules(rules);
+
+        parser
+            .parse_line(
+                r" 0:00 ClientUserinfoChanged: 2 n\Zeh\t\1",
+            )
+            .unwrap();
+        parser
+            .parse_line(
+                r" 0:00 ClientUserinfoChanged: 3 n\Mal\t\1",
+            )
+            .unwrap();
+        parser
+            .parse_line(
+                r" 0:00 ClientUserinfoChanged: 4 n\Bob\t\2",
+            )
+            .unwrap();
+
+        parser.parse_line(" 0:01 Kill: 2 4 7: Zeh killed Bob by MOD_ROCKET").unwrap();
+        parser.parse_line(" 0:02 Kill: 1022 4 19: <world> killed Bob by MOD_FALLING").unwrap();
+        parser.parse_line(" 0:03 Kill: 4 4 7: Bob killed Bob by MOD_ROCKET_SPLASH").unwrap();
+        parser.parse_line(" 0:04 Kill: 3 2 7: Mal killed Zeh by MOD_ROCKET").unwrap();
+
+        let report = parser
+            .parse_line(" 0:05 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(report.score_of("Zeh"), Some(2));
+        assert_eq!(report.score_of("Bob"), Some(-3));
+
+        // Killing a teammate is no kill either
+        let mal = report.stats_of("Mal").unwrap();
+        assert_eq!(report.score_of("Mal"), Some(-5));
+        assert_eq!(mal.kills, 0);
+        assert_eq!(mal.team_kills, 1);
+        assert_eq!(report.stats_of("Zeh").unwrap().deaths, 1);
+    }
+
+    /// Counts the items picked up and the railgun kills of every
+    /// game
+    #[derive(Default)]
+    struct ItemsAndRails {
+        games_started: u32,
+        items: u32,
+        rails: u32,
+    }
+
+    impl EventHandler for ItemsAndRails {
+        fn game_start(
+            &mut self,
+            _settings: Option<&GameSettings>,
+        ) {
+            self.games_started += 1;
+        }
+
+        fn event(
+            &mut self,
+            event: &TimedEvent,
+            _registry: &PlayerRegistry,
+        ) -> Result<()> {
+            if let Event::Item { .. } = event.event {
+                self.items += 1;
+            }
+
+            Ok(())
+        }
+
+        fn kill(&mut self, kill: &ResolvedKill) -> Result<()> {
+            if kill.kill.cause_of_death == CauseOfDeath::Railgun
+            {
+                self.rails += 1;
+            }
+
+            Ok(())
+        }
+
+        fn game_end(&mut self, report: &mut GameReport) {
+            report.custom.insert(
+                "items".into(),
+                serde_json::json!({
+                    "games_started": self.games_started,
+                    "items": self.items,
+                    "rails": self.rails,
+                }),
+            );
+
+            self.items = 0;
+            self.rails = 0;
+        }
+    }
+
+    #[test]
+    fn parser_dispatches_events_to_handlers() {
+        let mut parser = LogParser::new()
+            .with_handler(ItemsAndRails::default());
+
+        parser
+            .parse_line(" 0:00 InitGame: \\mapname\\q3dm17")
+            .unwrap();
+        parser
+            .parse_line(" 0:01 Item: 2 weapon_railgun")
+            .unwrap();
+        parser
+            .parse_line(" 0:02 Item: 3 weapon_railgun")
+            .unwrap();
+        parser.parse_line(" 0:03 Kill: 2 3 10: Zeh killed Mal by MOD_RAILGUN").unwrap();
+        parser.parse_line(" 0:04 Kill: 3 2 6: Mal killed Zeh by MOD_ROCKET").unwrap();
+
+        let report = parser
+            .parse_line(" 0:05 ShutdownGame:")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            report.custom["items"],
+            serde_json::json!({
+                "games_started": 1,
+                "items": 2,
+                "rails": 1,
+            })
+        );
+
+        // The built-in scores are still kept
+        assert_eq!(report.total_kills, 2);
+        assert_eq!(report.score_of("Zeh"), Some(1));
+    }
+
+    struct NoKillsAllowed;
+
+    impl EventHandler for NoKillsAllowed {
+        fn kill(&mut self, _kill: &ResolvedKill) -> Result<()> {
+            Err(Error::Assertion("no kills allowed"))
+        }
+    }
+
+    #[test]
+    fn lenient_parser_passes_handler_errors_through() {
+        let mut parser = LogParser::new()
+            .with_mode(ParseMode::Lenient)
+            .with_handler(NoKillsAllowed);
+
+        let result = parser.parse_line(
+            " 0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET",
+        );
+
+        assert!(matches!(result, Err(Error::Assertion(_))));
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    struct NoGamesAllowed;
+
+    impl EventHandler for NoGamesAllowed {
+        fn event(
+            &mut self,
+            event: &TimedEvent,
+            _registry: &PlayerRegistry,
+        ) -> Result<()> {
+            match event.event {
+                Event::InitGame(_) => {
+                    Err(Error::Assertion("no games allowed"))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn parser_keeps_reports_of_games_ended_by_failing_lines() {
+        let mut parser = LogParser::new();
+
+        parser
+            .parse_line(" 0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET")
+            .unwrap();
+
+        let mut parser = parser.with_handler(NoGamesAllowed);
+        let result = parser
+            .parse_line(r" 0:02 InitGame: \mapname\q3dm17");
+
+        let Err(Error::GameEnded { report, error }) = result
+        else {
+            panic!("expected the ended game, got {result:?}");
+        };
+        assert!(report.incomplete);
+        assert_eq!(report.total_kills, 1);
+        assert!(matches!(*error, Error::Assertion(_)));
     }
 }
@@ -1,17 +1,24 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use nom::{
     bytes::complete::{
-        tag, take_till1, take_until1, take_while, take_while_m_n,
+        tag, take_till, take_till1, take_until1, take_while,
+        take_while_m_n,
     },
-    character::complete::char,
+    character::complete::{char, digit1},
+    combinator::{map_opt, map_res, opt, recognize, verify},
+    multi::separated_list0,
     sequence::{
         delimited, preceded, separated_pair, terminated, tuple,
     },
     IResult,
 };
 
-use crate::CauseOfDeath;
+use crate::{CauseOfDeath, ClientId};
+
+/// The keys and values of an "info string", in order of
+/// appearance
+pub type InfoString<'a> = Vec<(&'a str, &'a str)>;
 
 #[derive(Debug, PartialEq, Eq)]
 /// The information contained in a kill message, such as
@@ -24,11 +31,32 @@ pub struct KillMessage<'a> {
     pub cause_of_death: CauseOfDeath,
 }
 
-// TODO: breaks if `attacker`'s name contains " killed " and if
-//       `victim`'s name contains " by "
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The numbers that precede a kill message, such as
+/// "1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT"
+///  ∧∧∧∧ ∧ ∧∧
+///    │  │  └ cause of death
+///    │  └ victim's client id
+///    └ attacker's client id
+pub struct KillMetadata {
+    pub attacker_id: ClientId,
+    pub victim_id: ClientId,
+    /// The game's own number for the cause of death.
+    pub cause_of_death_id: u32,
+}
+
+/// Parses a kill message.
+///
+/// The victim's name is allowed to contain " by ", but an
+/// attacker whose name contains " killed " gets split at the
+/// wrong spot. `LogParser` relies on the client ids of
+/// [`KillMetadata`] to attribute kills, so this is only a
+/// problem when the ids can't be resolved.
 pub fn parse_kill_message(
     input: &str,
-) -> IResult<&str, KillMessage> {
+) -> IResult<&str, KillMessage<'_>> {
+    use nom::error::{ContextError, Error, ErrorKind};
+
     const KILLED_TAG: &str = " killed ";
     const BY_TAG: &str = " by ";
 
@@ -37,18 +65,25 @@ pub fn parse_kill_message(
         terminated(take_until1(KILLED_TAG), tag(KILLED_TAG)),
     )(input)?;
 
-    let (rest, victim) = preceded(
-        parse_ws,
-        terminated(take_until1(BY_TAG), tag(BY_TAG)),
-    )(rest)?;
+    let (rest, victim_and_cause) =
+        preceded(parse_ws, take_till1(is_newline))(rest)?;
+
+    // The cause of death comes after the last " by ", since
+    // victims may have it in their names
+    let (victim, cause_of_death) = victim_and_cause
+        .rsplit_once(BY_TAG)
+        .ok_or_else(|| {
+            nom::Err::Error(Error::new(
+                victim_and_cause,
+                ErrorKind::TakeUntil,
+            ))
+        })?;
 
-    let (rest, cause_of_death) =
-        take_till1(is_ascii_whitespace)(rest)?;
+    let (trailing_ws, cause_of_death) =
+        take_till1(is_ascii_whitespace)(cause_of_death)?;
 
     let cause_of_death = CauseOfDeath::from_str(cause_of_death)
         .map_err(|_| {
-            use nom::error::{ContextError, Error, ErrorKind};
-
             nom::Err::Error(ContextError::add_context(
                 cause_of_death,
                 "Not a valid CauseOfDeath",
@@ -62,24 +97,178 @@ pub fn parse_kill_message(
         cause_of_death,
     };
 
+    // Hand back whatever came after the cause of death, which
+    // immediately precedes `rest`
+    let rest =
+        &input[input.len() - rest.len() - trailing_ws.len()..];
+
     Ok((rest, kill_message))
 }
 
-pub fn parse_kill(input: &str) -> IResult<&str, KillMessage> {
-    let (rest, _) = parse_kill_metadata(input)?;
-    parse_kill_message(rest)
+/// Parses the information that follows a `ClientUserinfoChanged`
+/// header, as shown below:
+///
+///    ∨ ∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨
+/// ": 2 n\Isgalamido\t\0\model\xian/default"
+///
+/// Returns the client id and the keys and values of the
+/// userinfo
+pub fn parse_userinfo_changed(
+    input: &str,
+) -> IResult<&str, (ClientId, InfoString<'_>)> {
+    let (rest, client_id) = parse_client_id(input)?;
+    let (rest, userinfo) =
+        preceded(char(' '), parse_info_string)(rest)?;
+
+    Ok((rest, (client_id, userinfo)))
+}
+
+/// Parses the server settings that follow an `InitGame` header,
+/// as shown below:
+///
+///    ∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨
+/// ": \mapname\q3dm17\g_gametype\0\fraglimit\20"
+///
+/// Returns the keys and values of every cvar logged
+pub fn parse_init_game(
+    input: &str,
+) -> IResult<&str, InfoString<'_>> {
+    preceded(parse_colon, preceded(parse_ws, parse_info_string))(
+        input,
+    )
+}
+
+/// Parses the client id that follows headers such as
+/// `ClientConnect` or `ClientDisconnect`, e.g. `": 2"`
+pub fn parse_client_id(input: &str) -> IResult<&str, ClientId> {
+    preceded(parse_colon, preceded(parse_ws, parse_number))(
+        input,
+    )
+}
+
+/// Parses the information that follows an `Item` header, e.g.
+/// `": 4 weapon_rocketlauncher"`
+///
+/// Returns the client id and the item's class name, which mods
+/// are free to extend
+pub fn parse_item(
+    input: &str,
+) -> IResult<&str, (ClientId, &str)> {
+    let (rest, client_id) = parse_client_id(input)?;
+    let (rest, class_name) = preceded(
+        parse_ws,
+        take_till1(is_ascii_whitespace),
+    )(rest)?;
+
+    Ok((rest, (client_id, class_name)))
+}
+
+/// Parses the information that follows a `say` header, e.g.
+/// `": Isgalamido: team blue"`
+///
+/// Returns the name of the player and what they said. Names
+/// containing `": "` get split at the wrong spot.
+pub fn parse_say(input: &str) -> IResult<&str, (&str, &str)> {
+    preceded(
+        parse_colon,
+        separated_pair(
+            preceded(char(' '), take_until1(": ")),
+            tag(": "),
+            take_till(is_newline),
+        ),
+    )(input)
+}
+
+/// Parses the reason that follows an `Exit` header, e.g.
+/// `": Fraglimit hit."`
+pub fn parse_exit(input: &str) -> IResult<&str, &str> {
+    preceded(
+        parse_colon,
+        preceded(parse_ws, take_till(is_newline)),
+    )(input)
 }
 
-/// Parses timestamps in the form of `MM:ss`.
+/// Parses the team scores that follow a `red` header, e.g.
+/// `":8  blue:6"`
 ///
-/// `MM` and `ss` are returned as string slices.
-pub fn parse_timestamp(
+/// Returns the scores of the red and blue teams
+pub fn parse_team_score(
     input: &str,
-) -> IResult<&str, (&str, &str)> {
+) -> IResult<&str, (i32, i32)> {
     separated_pair(
-        preceded(parse_ws, parse_decimals),
-        char(':'),
-        parse_decimals,
+        preceded(char(':'), parse_signed),
+        parse_ws,
+        preceded(tag("blue:"), parse_signed),
+    )(input)
+}
+
+/// Parses the information that follows a `score` header, as
+/// shown below:
+///
+///    ∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨∨
+/// ": 20  ping: 4  client: 4 Zeh"
+///
+/// Returns the score, the ping, the client id and the name of
+/// the player
+pub fn parse_score(
+    input: &str,
+) -> IResult<&str, (i32, u32, ClientId, &str)> {
+    let (rest, (score, ping, client_id, name)) = tuple((
+        preceded(parse_colon, preceded(parse_ws, parse_signed)),
+        preceded(
+            delimited(parse_ws, tag("ping:"), parse_ws),
+            parse_number,
+        ),
+        preceded(
+            delimited(parse_ws, tag("client:"), parse_ws),
+            parse_number,
+        ),
+        preceded(char(' '), take_till(is_newline)),
+    ))(input)?;
+
+    Ok((rest, (score, ping, client_id, name)))
+}
+
+/// Parses an "info string": a sequence of backslash-delimited
+/// keys and values, such as `\mapname\q3dm17\fraglimit\20`.
+///
+/// The leading backslash is optional.
+pub fn parse_info_string(
+    input: &str,
+) -> IResult<&str, InfoString<'_>> {
+    let is_delimiter =
+        |ch: char| matches!(ch, '\\' | '\r' | '\n');
+    let field = || take_till(is_delimiter);
+
+    preceded(
+        opt(char('\\')),
+        separated_list0(
+            char('\\'),
+            separated_pair(field(), char('\\'), field()),
+        ),
+    )(input)
+}
+
+/// Parses timestamps in the form of `MM:ss` into the time
+/// since the server started.
+///
+/// Minutes keep counting past 59 rather than rolling over into
+/// hours, as in `981:06`.
+pub fn parse_timestamp(input: &str) -> IResult<&str, Duration> {
+    let into_duration = |(minutes, seconds): (u64, u64)| {
+        let seconds =
+            minutes.checked_mul(60)?.checked_add(seconds)?;
+
+        Some(Duration::from_secs(seconds))
+    };
+
+    map_opt(
+        separated_pair(
+            preceded(parse_ws, parse_number),
+            char(':'),
+            verify(parse_number, |&seconds| seconds < 60),
+        ),
+        into_duration,
     )(input)
 }
 
@@ -98,26 +287,31 @@ pub fn parse_spacer_line(input: &str) -> IResult<&str, &str> {
 ///
 ///            ∨∨∨∨∨∨∨∨∨∨∨∨
 /// "20:54 Kill: 1022 2 22: <world> killed Isgalamido by"
-///
-/// Returns the three numbers within the metadata as string
-/// slices
 pub fn parse_kill_metadata(
     input: &str,
-) -> IResult<&str, (&str, &str, &str)> {
+) -> IResult<&str, KillMetadata> {
     let parse_triplet = tuple((
-        preceded(parse_ws, parse_decimals),
+        preceded(parse_ws, parse_number),
         char(' '),
-        parse_decimals,
+        parse_number,
         char(' '),
-        parse_decimals,
+        parse_number,
     ));
 
-    let (rest, (first_digit, _, second_digit, _, third_digit)) =
-        delimited(parse_colon, parse_triplet, parse_colon)(
-            input,
-        )?;
+    let (
+        rest,
+        (attacker_id, _, victim_id, _, cause_of_death_id),
+    ) = delimited(parse_colon, parse_triplet, parse_colon)(
+        input,
+    )?;
 
-    Ok((rest, (first_digit, second_digit, third_digit)))
+    let metadata = KillMetadata {
+        attacker_id,
+        victim_id,
+        cause_of_death_id,
+    };
+
+    Ok((rest, metadata))
 }
 
 #[inline(always)]
@@ -126,10 +320,16 @@ fn parse_colon(input: &str) -> IResult<&str, char> {
 }
 
 #[inline(always)]
-fn parse_decimals(input: &str) -> IResult<&str, &str> {
-    let is_decimal = |ch: char| ch.is_digit(10);
+fn parse_number<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(input)
+}
 
-    take_while(is_decimal)(input)
+#[inline(always)]
+fn parse_signed(input: &str) -> IResult<&str, i32> {
+    map_res(
+        recognize(preceded(opt(char('-')), digit1)),
+        str::parse,
+    )(input)
 }
 
 /// Parses leading whitespace
@@ -138,6 +338,11 @@ pub fn parse_ws(input: &str) -> IResult<&str, &str> {
     take_while(is_ascii_whitespace)(input)
 }
 
+#[inline(always)]
+const fn is_newline(ch: char) -> bool {
+    matches!(ch, '\r' | '\n')
+}
+
 #[inline(always)]
 const fn is_ascii_whitespace(ch: char) -> bool {
     matches!(ch, '\t' | '\n' | '\x0C' | '\r' | ' ')
@@ -145,10 +350,14 @@ const fn is_ascii_whitespace(ch: char) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
 
     use super::{
-        parse_kill_message, parse_kill_metadata,
-        parse_timestamp, KillMessage,
+        parse_exit, parse_info_string, parse_init_game,
+        parse_item, parse_kill_message, parse_kill_metadata,
+        parse_say, parse_score, parse_team_score,
+        parse_timestamp, parse_userinfo_changed, KillMessage,
+        KillMetadata,
     };
     use crate::CauseOfDeath;
 
@@ -183,24 +392,150 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_victims_with_by_in_their_names() {
+        assert_eq!(
+            parse_kill_message(
+                "Zeh killed Stand by Me by MOD_RAILGUN\n"
+            ),
+            Ok((
+                "\n",
+                KillMessage {
+                    attacker: "Zeh",
+                    victim: "Stand by Me",
+                    cause_of_death: CauseOfDeath::Railgun,
+                }
+            ))
+        );
+    }
+
     #[test]
     fn parses_timestamps() {
         assert_eq!(
             parse_timestamp(" 5:40"),
-            Ok(("", ("5", "40")))
+            Ok(("", Duration::from_secs(5 * 60 + 40)))
         );
 
         assert_eq!(
             parse_timestamp("20:00 "),
-            Ok((" ", ("20", "00")))
+            Ok((" ", Duration::from_secs(20 * 60)))
+        );
+
+        // Minutes don't roll over into hours
+        assert_eq!(
+            parse_timestamp("981:06"),
+            Ok(("", Duration::from_secs(981 * 60 + 6)))
         );
+
+        assert!(parse_timestamp("1:60").is_err());
+        assert!(parse_timestamp(":30").is_err());
+    }
+
+    #[test]
+    fn parses_userinfo_changed() {
+        assert_eq!(
+            parse_userinfo_changed(
+                r": 2 n\Dono da Bola\t\0\g_redteam\\c1\5"
+            ),
+            Ok((
+                "",
+                (
+                    2,
+                    vec![
+                        ("n", "Dono da Bola"),
+                        ("t", "0"),
+                        ("g_redteam", ""),
+                        ("c1", "5")
+                    ]
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_init_game() {
+        assert_eq!(
+            parse_init_game(r": \mapname\q3dm17\g_gametype\0"),
+            Ok((
+                "",
+                vec![("mapname", "q3dm17"), ("g_gametype", "0")]
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_info_strings() {
+        assert_eq!(
+            parse_info_string(
+                "\\mapname\\q3dm17\\fraglimit\\20\n"
+            ),
+            Ok((
+                "\n",
+                vec![("mapname", "q3dm17"), ("fraglimit", "20")]
+            ))
+        );
+
+        assert_eq!(parse_info_string(""), Ok(("", vec![])));
     }
 
     #[test]
     fn parses_kill_metadata() {
         assert_eq!(
             parse_kill_metadata(": 1022 2 22:"),
-            Ok(("", ("1022", "2", "22")))
+            Ok((
+                "",
+                KillMetadata {
+                    attacker_id: 1022,
+                    victim_id: 2,
+                    cause_of_death_id: 22
+                }
+            ))
         )
     }
+
+    #[test]
+    fn parses_items() {
+        assert_eq!(
+            parse_item(": 5 team_CTF_redflag\n"),
+            Ok(("\n", (5, "team_CTF_redflag")))
+        );
+        assert_eq!(
+            parse_item(": 5 item_banana"),
+            Ok(("", (5, "item_banana")))
+        );
+
+        assert!(parse_item(": 5 ").is_err());
+    }
+
+    #[test]
+    fn parses_say() {
+        assert_eq!(
+            parse_say(": Oootsimo: team red: now"),
+            Ok(("", ("Oootsimo", "team red: now")))
+        );
+    }
+
+    #[test]
+    fn parses_exit() {
+        assert_eq!(
+            parse_exit(": Timelimit hit.\n"),
+            Ok(("\n", "Timelimit hit."))
+        );
+    }
+
+    #[test]
+    fn parses_team_scores() {
+        assert_eq!(
+            parse_team_score(":8  blue:-1"),
+            Ok(("", (8, -1)))
+        );
+    }
+
+    #[test]
+    fn parses_scores() {
+        assert_eq!(
+            parse_score(": -3  ping: 4  client: 4 Dono da Bola"),
+            Ok(("", (-3, 4, 4, "Dono da Bola")))
+        );
+    }
 }
@@ -0,0 +1,580 @@
+use std::{io::BufRead, time::Duration};
+
+use nom::{error::ErrorKind, Finish, IResult, Offset};
+
+use super::{
+    combinator::{
+        parse_client_id, parse_exit, parse_init_game,
+        parse_item, parse_kill_message, parse_kill_metadata,
+        parse_say, parse_score, parse_team_score,
+        parse_userinfo_changed,
+    },
+    header::{parse_timed_header, Header},
+    trim_line_terminator,
+};
+use crate::{
+    reader::{decode_line, LogLine},
+    CauseOfDeath, ClientId, ClientInfo, Error, GameSettings,
+    InvalidLineError, Item, ParseError, Result,
+};
+
+/// A kill, as logged by lines such as
+/// `Kill: 3 2 6: Isgalamido killed Mocinha by MOD_ROCKET`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Kill {
+    pub attacker_id: ClientId,
+    pub victim_id: ClientId,
+    /// The attacker's name, as logged.
+    pub attacker: String,
+    /// The victim's name, as logged.
+    pub victim: String,
+    pub cause_of_death: CauseOfDeath,
+}
+
+impl Kill {
+    /// The kill message, as logged.
+    pub fn message(&self) -> String {
+        format!(
+            "{} killed {} by {}",
+            self.attacker, self.victim, self.cause_of_death
+        )
+    }
+}
+
+/// Something that happened in the server, as logged by a single
+/// line of the log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A game started with the given settings.
+    InitGame(GameSettings),
+    ClientConnect {
+        client_id: ClientId,
+    },
+    /// A client joined or changed their settings, such as their
+    /// name or team.
+    ClientUserinfoChanged {
+        client_id: ClientId,
+        info: ClientInfo,
+    },
+    /// A client entered the game.
+    ClientBegin {
+        client_id: ClientId,
+    },
+    ClientDisconnect {
+        client_id: ClientId,
+    },
+    /// A client picked up an item, such as `weapon_railgun`.
+    Item {
+        client_id: ClientId,
+        /// The item, unless it's one added by a mod.
+        item: Option<Item>,
+        /// The item's class name, as logged.
+        class_name: String,
+    },
+    Kill(Kill),
+    /// A player said something in the chat.
+    Say {
+        name: String,
+        message: String,
+    },
+    /// The game is over, e.g. because the fraglimit was hit.
+    Exit {
+        reason: String,
+    },
+    /// The final scores of both teams, in team-based games.
+    TeamScore {
+        red: i32,
+        blue: i32,
+    },
+    /// A player's final score, as reckoned by the server.
+    Score {
+        score: i32,
+        ping: u32,
+        client_id: ClientId,
+        name: String,
+    },
+    ShutdownGame,
+    /// The line of hyphens that surrounds the start and the end
+    /// of every game.
+    Spacer,
+}
+
+/// An event, along with when it was logged
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedEvent {
+    /// The time since the server started, as logged.
+    pub timestamp: Duration,
+    /// The time since the game this event belongs to started,
+    /// if its `InitGame` line was seen.
+    pub game_time: Option<Duration>,
+    pub event: Event,
+}
+
+impl TimedEvent {
+    /// Parses a single line of a log into the event it logs.
+    ///
+    /// There's no game to time it against, unless it's the
+    /// `InitGame` line that starts one.
+    pub fn parse(line: &str) -> Result<Self> {
+        let mut event = parse_event(line).map_err(|err| {
+            err.locate(line.into(), None, 0, 0)
+        })?;
+        GameClock::default().stamp(&mut event);
+
+        Ok(event)
+    }
+}
+
+/// Times events against the start of the game they belong to
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct GameClock {
+    /// When the current game started, since the server started.
+    start: Option<Duration>,
+}
+
+impl GameClock {
+    /// Stamps the given event with the time since its game
+    /// started.
+    ///
+    /// Timestamps start over from `0:00` whenever the server
+    /// restarts, which shows up as time going backwards within
+    /// a game, so events are never timed before its start.
+    pub fn stamp(&mut self, event: &mut TimedEvent) {
+        if let Event::InitGame(_) = event.event {
+            self.start = Some(event.timestamp);
+        }
+
+        event.game_time = self
+            .start
+            .map(|start| event.timestamp.saturating_sub(start));
+
+        if let Event::ShutdownGame = event.event {
+            self.start = None;
+        }
+    }
+}
+
+/// An iterator over the events of a log, yielding one event (or
+/// error) per line
+///
+/// Malformed lines don't end the iteration, so that they can be
+/// skipped, but errors reading the log do.
+pub struct Events<R> {
+    reader: R,
+    /// The name of the log, if given.
+    source: Option<String>,
+    /// The line being read, as raw bytes.
+    bytes: Vec<u8>,
+    line: String,
+    /// How many lines were read so far.
+    lines_read: usize,
+    /// How many bytes were read so far.
+    bytes_read: usize,
+    /// Whether the log is over, or failed to be read.
+    done: bool,
+    clock: GameClock,
+}
+
+impl<R: BufRead> Events<R> {
+    /// Reads events out of the given log.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            source: None,
+            bytes: Vec::with_capacity(1024),
+            line: String::with_capacity(1024),
+            lines_read: 0,
+            bytes_read: 0,
+            done: false,
+            clock: GameClock::default(),
+        }
+    }
+
+    /// Sets the name of the log, such as its path, which errors
+    /// are tagged with.
+    pub fn with_source(
+        mut self,
+        source: impl Into<String>,
+    ) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+}
+
+impl<R: BufRead> Iterator for Events<R> {
+    type Item = Result<TimedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        self.bytes.clear();
+        match self.reader.read_until(b'\n', &mut self.bytes) {
+            Ok(0) => {
+                self.done = true;
+                return None;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err.into()));
+            }
+        }
+
+        let line = decode_line(&self.bytes, &mut self.line);
+
+        let event = match parse_event(line.text) {
+            Ok(mut event) => {
+                self.clock.stamp(&mut event);
+                Ok(event)
+            }
+            Err(err) => Err(err.locate(
+                line,
+                self.source.as_deref(),
+                self.lines_read,
+                self.bytes_read,
+            )),
+        };
+        self.lines_read += 1;
+        self.bytes_read += line.raw_len;
+
+        Some(event)
+    }
+}
+
+/// Why a line couldn't be parsed into an event
+pub(crate) enum EventError<'a> {
+    /// The line doesn't follow the syntax of the log.
+    Syntax(nom::Err<nom::error::Error<&'a str>>),
+    /// The line is well-formed, but holds an invalid value.
+    Invalid(Error),
+}
+
+impl EventError<'_> {
+    /// Converts this error into a `crate::Error`, pointing at
+    /// where `line` failed to parse given the amount of lines
+    /// and bytes of the log that precede it.
+    pub(crate) fn locate(
+        self,
+        line: LogLine,
+        source: Option<&str>,
+        lines_read: usize,
+        bytes_read: usize,
+    ) -> Error {
+        let error = match self {
+            EventError::Syntax(error) => error,
+            EventError::Invalid(error) => {
+                return Error::InvalidLine(InvalidLineError {
+                    source: source.map(Into::into),
+                    line: lines_read + 1,
+                    offset: bytes_read,
+                    line_text: trim_line_terminator(line.text)
+                        .into(),
+                    error: Box::new(error),
+                })
+            }
+        };
+
+        let result: IResult<_, &str> = Err(error);
+        let result = result.finish();
+
+        // This result is guaranteed to be an error,
+        // so unwrap_err will never fail
+        let error = result.unwrap_err();
+
+        // Every parser works on slices of `line`
+        let position = line.text.offset(error.input);
+        let raw_position = line.raw_position(position);
+
+        Error::ParsingError(ParseError {
+            source: source.map(Into::into),
+            line: lines_read + 1,
+            column: raw_position + 1,
+            char_column: line.text[..position].chars().count()
+                + 1,
+            offset: bytes_read + raw_position,
+            line_text: trim_line_terminator(line.text).into(),
+            kind: error.code,
+        })
+    }
+}
+
+impl<'a> From<nom::Err<nom::error::Error<&'a str>>>
+    for EventError<'a>
+{
+    fn from(
+        error: nom::Err<nom::error::Error<&'a str>>,
+    ) -> Self {
+        EventError::Syntax(error)
+    }
+}
+
+impl From<Error> for EventError<'_> {
+    fn from(error: Error) -> Self {
+        EventError::Invalid(error)
+    }
+}
+
+/// Parses a single line of a log into the event it logs.
+pub(crate) fn parse_event(
+    line: &str,
+) -> std::result::Result<TimedEvent, EventError<'_>> {
+    let (rest, (timestamp, header)) = parse_timed_header(line)?;
+
+    let event = match header {
+        Header::InitGame => {
+            let (_rest, cvars) = parse_init_game(rest)?;
+            Event::InitGame(GameSettings::from_cvars(cvars))
+        }
+        Header::Kill => Event::Kill(parse_kill(rest)?.1),
+        Header::ClientBegin => {
+            let (_rest, client_id) = parse_client_id(rest)?;
+            Event::ClientBegin { client_id }
+        }
+        Header::ClientUserinfoChanged => {
+            let (_rest, (client_id, userinfo)) =
+                parse_userinfo_changed(rest)?;
+            let info = ClientInfo::from_userinfo(userinfo);
+
+            Event::ClientUserinfoChanged { client_id, info }
+        }
+        Header::ClientConnect => {
+            let (_rest, client_id) = parse_client_id(rest)?;
+            Event::ClientConnect { client_id }
+        }
+        Header::ClientDisconnect => {
+            let (_rest, client_id) = parse_client_id(rest)?;
+            Event::ClientDisconnect { client_id }
+        }
+        Header::ShutdownGame => Event::ShutdownGame,
+        Header::Score => {
+            let (_rest, (score, ping, client_id, name)) =
+                parse_score(rest)?;
+
+            Event::Score {
+                score,
+                ping,
+                client_id,
+                name: name.into(),
+            }
+        }
+        Header::Item => {
+            let (_rest, (client_id, class_name)) =
+                parse_item(rest)?;
+            Event::Item {
+                client_id,
+                item: class_name.parse().ok(),
+                class_name: class_name.into(),
+            }
+        }
+        Header::Exit => {
+            let (_rest, reason) = parse_exit(rest)?;
+            Event::Exit {
+                reason: reason.into(),
+            }
+        }
+        Header::Red => {
+            let (_rest, (red, blue)) = parse_team_score(rest)?;
+            Event::TeamScore { red, blue }
+        }
+        Header::Say => {
+            let (_rest, (name, message)) = parse_say(rest)?;
+            Event::Say {
+                name: name.into(),
+                message: message.into(),
+            }
+        }
+        Header::Spacer => Event::Spacer,
+    };
+
+    Ok(TimedEvent {
+        timestamp,
+        game_time: None,
+        event,
+    })
+}
+
+/// Parses the information that follows a `Kill` header, erring
+/// on anything that comes after the kill message.
+fn parse_kill(input: &str) -> IResult<&str, Kill> {
+    let (text, metadata) = parse_kill_metadata(input)?;
+    let (rest, message) = parse_kill_message(text)?;
+
+    // Point at whatever comes after the kill message
+    let trailing = rest.trim_start();
+    if !trailing.is_empty() {
+        let error =
+            nom::error::Error::new(trailing, ErrorKind::Eof);
+        return Err(nom::Err::Error(error));
+    }
+
+    let kill = Kill {
+        attacker_id: metadata.attacker_id,
+        victim_id: metadata.victim_id,
+        attacker: message.attacker.into(),
+        victim: message.victim.into(),
+        cause_of_death: message.cause_of_death,
+    };
+
+    Ok((rest, kill))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, time::Duration};
+
+    use super::{Event, EventError, Events, Kill, TimedEvent};
+    use crate::{CauseOfDeath, Error, Item};
+
+    #[test]
+    fn parses_every_kind_of_line() {
+        let parse =
+            |line| TimedEvent::parse(line).unwrap().event;
+
+        assert_eq!(
+            parse(" 20:34 ClientConnect: 2"),
+            Event::ClientConnect { client_id: 2 }
+        );
+        assert_eq!(
+            parse("  1:19 Item: 5 team_CTF_redflag"),
+            Event::Item {
+                client_id: 5,
+                item: Some(Item::RedFlag),
+                class_name: "team_CTF_redflag".into()
+            }
+        );
+        assert_eq!(
+            parse("  1:20 Item: 5 weapon_hmg"),
+            Event::Item {
+                client_id: 5,
+                item: None,
+                class_name: "weapon_hmg".into()
+            }
+        );
+        assert_eq!(
+            parse("981:21 say: Oootsimo: team red"),
+            Event::Say {
+                name: "Oootsimo".into(),
+                message: "team red".into()
+            }
+        );
+        assert_eq!(
+            parse(" 15:00 Exit: Timelimit hit."),
+            Event::Exit {
+                reason: "Timelimit hit.".into()
+            }
+        );
+        assert_eq!(
+            parse(" 10:12 red:8  blue:6"),
+            Event::TeamScore { red: 8, blue: 6 }
+        );
+        assert_eq!(
+            parse(" 11:57 score: 20  ping: 4  client: 4 Zeh"),
+            Event::Score {
+                score: 20,
+                ping: 4,
+                client_id: 4,
+                name: "Zeh".into()
+            }
+        );
+        assert_eq!(
+            parse("  0:00 ------------------------------------------------------------"),
+            Event::Spacer
+        );
+
+        let event = TimedEvent::parse(
+            " 21:42 Kill: 3 2 6: Isgalamido killed Mocinha by MOD_ROCKET",
+        )
+        .unwrap();
+        assert_eq!(
+            event.timestamp,
+            Duration::from_secs(21 * 60 + 42)
+        );
+        assert_eq!(event.game_time, None);
+        assert_eq!(
+            event.event,
+            Event::Kill(Kill {
+                attacker_id: 3,
+                victim_id: 2,
+                attacker: "Isgalamido".into(),
+                victim: "Mocinha".into(),
+                cause_of_death: CauseOfDeath::Rocket,
+            })
+        );
+    }
+
+    #[test]
+    fn streams_events_out_of_a_log() {
+        let log = " 20:00 InitGame: \\mapname\\q3dm17\n\
+                   20:01 ClientBegin: 2\n\
+                   20:02 Kill: 1022 2 19: <world> killed Zeh by MOD_BANANA\n\
+                   20:03 ShutdownGame:\n";
+
+        let events: Vec<_> = Events::new(Cursor::new(log))
+            .with_source("games.log")
+            .collect();
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0],
+            Ok(TimedEvent {
+                event: Event::InitGame(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            &events[2],
+            Err(Error::ParsingError(err))
+                if err.line == 3 && err.source.as_deref() == Some("games.log")
+        ));
+        assert!(matches!(
+            events[3],
+            Ok(TimedEvent {
+                event: Event::ShutdownGame,
+                ..
+            })
+        ));
+
+        // Events are timed against the start of their game
+        let game_time = |idx: usize| {
+            events[idx].as_ref().unwrap().game_time.unwrap()
+        };
+        assert_eq!(game_time(1), Duration::from_secs(1));
+        assert_eq!(game_time(3), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn locates_invalid_values() {
+        let line =
+            " 20:34 ClientUserinfoChanged: 2 n\\Zeh\\hc\\x\n";
+        let error =
+            EventError::Invalid(Error::InvalidInfoValue {
+                key: "hc",
+                value: "x".into(),
+            })
+            .locate(
+                line.into(),
+                Some("games.log"),
+                2,
+                100,
+            );
+
+        let Error::InvalidLine(err) = &error else {
+            panic!("expected an invalid line, got {error:?}");
+        };
+        assert_eq!(err.source.as_deref(), Some("games.log"));
+        assert_eq!(err.line, 3);
+        assert_eq!(err.offset, 100);
+        assert_eq!(
+            err.line_text,
+            " 20:34 ClientUserinfoChanged: 2 n\\Zeh\\hc\\x"
+        );
+        assert_eq!(
+            error.to_string(),
+            "Invalid line at games.log, line 3 (byte 100): \
+             Invalid value for `hc`: x\n  |\n3 |  20:34 \
+             ClientUserinfoChanged: 2 n\\Zeh\\hc\\x"
+        );
+    }
+}
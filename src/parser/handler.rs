@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use super::{
+    event::{Kill, TimedEvent},
+    score_keeper::ScoreKeeper,
+    WORLD_ID,
+};
+use crate::{
+    ClientId, ClientInfo, GameReport, GameSettings,
+    PlayerRegistry, Result,
+};
+
+/// Collects statistics out of the events of a log
+///
+/// Every callback does nothing by default, so that handlers
+/// only implement the ones they care about. Handlers are
+/// registered through
+/// [`LogParser::with_handler`](crate::LogParser::with_handler),
+/// and publish whatever they collected through the `custom`
+/// section of every [`GameReport`].
+pub trait EventHandler {
+    /// Called when a game starts, with its settings if its
+    /// `InitGame` line was seen.
+    fn game_start(&mut self, _settings: Option<&GameSettings>) {}
+
+    /// Called for every line of the log, before the more
+    /// specific callbacks below.
+    fn event(
+        &mut self,
+        _event: &TimedEvent,
+        _registry: &PlayerRegistry,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for every kill, once the players involved were
+    /// identified.
+    fn kill(&mut self, _kill: &ResolvedKill) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called whenever a client's settings change, such as when
+    /// they join the game or change their name.
+    fn userinfo_changed(
+        &mut self,
+        _client_id: ClientId,
+        _info: &ClientInfo,
+        _previous: Option<&ClientInfo>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when a game ends, to add to its report.
+    fn game_end(&mut self, _report: &mut GameReport) {}
+}
+
+/// A kill, along with the names of the players involved as
+/// given by their client ids
+#[derive(Clone, Copy, Debug)]
+pub struct ResolvedKill<'a> {
+    pub kill: &'a Kill,
+    /// The time since the server started, as logged.
+    pub timestamp: Duration,
+    /// The time since the game started, if its `InitGame` line
+    /// was seen.
+    pub game_time: Option<Duration>,
+    /// The attacker's name, or `<world>` for kills caused by
+    /// the map.
+    pub attacker: &'a str,
+    /// The victim's name.
+    pub victim: &'a str,
+    /// Whether the attacker killed a teammate.
+    pub team_kill: bool,
+}
+
+impl ResolvedKill<'_> {
+    /// Whether the map caused this kill, such as by falling or
+    /// drowning.
+    pub fn by_world(&self) -> bool {
+        self.kill.attacker_id == WORLD_ID
+    }
+
+    /// Whether the victim killed themselves.
+    ///
+    /// Told by client ids, since players may share a name.
+    pub fn self_kill(&self) -> bool {
+        self.kill.attacker_id == self.kill.victim_id
+    }
+}
+
+/// The handlers registered with a parser, starting with the
+/// built-in one
+#[derive(Default)]
+pub(crate) struct Handlers {
+    /// Keeps the scores and the causes of death of reports.
+    pub score_keeper: ScoreKeeper,
+    custom: Vec<Box<dyn EventHandler>>,
+}
+
+impl Handlers {
+    pub fn push(&mut self, handler: Box<dyn EventHandler>) {
+        self.custom.push(handler);
+    }
+
+    /// Iterates over every handler, in order of registration.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl Iterator<Item = &mut (dyn EventHandler + 'static)>
+    {
+        let score_keeper: &mut (dyn EventHandler + 'static) =
+            &mut self.score_keeper;
+
+        std::iter::once(score_keeper).chain(
+            self.custom.iter_mut().map(|handler| &mut **handler),
+        )
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use nom::{
     branch::alt, bytes::complete::tag, combinator::value,
     IResult,
@@ -33,11 +35,14 @@ pub enum Header {
     Spacer,
 }
 
-pub fn parse_header(input: &str) -> IResult<&str, Header> {
-    let (rest, _) = parse_timestamp(input)?;
+/// Parses the timestamp and the header a line starts with.
+pub fn parse_timed_header(
+    input: &str,
+) -> IResult<&str, (Duration, Header)> {
+    let (rest, timestamp) = parse_timestamp(input)?;
     let (rest, _ws) = parse_ws(rest)?;
 
-    alt((
+    let (rest, header) = alt((
         value(Header::Item, tag("Item")),
         value(Header::Kill, tag("Kill")),
         value(Header::InitGame, tag("InitGame")),
@@ -54,12 +59,40 @@ pub fn parse_header(input: &str) -> IResult<&str, Header> {
         value(Header::Score, tag("score")),
         value(Header::Exit, tag("Exit")),
         value(Header::Spacer, parse_spacer_line),
-    ))(rest)
+    ))(rest)?;
+
+    Ok((rest, (timestamp, header)))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_header, Header};
+    use std::time::Duration;
+
+    use nom::IResult;
+
+    use super::{parse_timed_header, Header};
+
+    /// Parses the header of a line, leaving its timestamp out
+    fn parse_header(input: &str) -> IResult<&str, Header> {
+        let (rest, (_timestamp, header)) =
+            parse_timed_header(input)?;
+
+        Ok((rest, header))
+    }
+
+    #[test]
+    fn parses_timestamps_along_with_headers() {
+        assert_eq!(
+            parse_timed_header("981:06 ShutdownGame:"),
+            Ok((
+                ":",
+                (
+                    Duration::from_secs(981 * 60 + 6),
+                    Header::ShutdownGame
+                )
+            ))
+        );
+    }
 
     #[test]
     fn parses_headers() {
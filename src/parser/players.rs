@@ -0,0 +1,84 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::PlayerStats;
+
+/// A player seen during a game
+#[derive(Debug)]
+pub struct Player {
+    pub name: Rc<str>,
+    pub score: i32,
+    pub stats: PlayerStats,
+}
+
+/// The players seen during a game, kept in order of
+/// first appearance.
+#[derive(Default)]
+pub struct Players {
+    players: Vec<Player>,
+    /// Maps each player's name to their position in
+    /// `players`.
+    indices: HashMap<Rc<str>, usize>,
+}
+
+impl Players {
+    /// Returns the player with the given name, inserting them
+    /// if this is their first appearance.
+    pub fn get_or_insert(&mut self, name: &str) -> &mut Player {
+        let idx = match self.indices.get(name) {
+            Some(&idx) => idx,
+            None => {
+                let name: Rc<str> = Rc::from(name);
+                let idx = self.players.len();
+
+                self.indices.insert(name.clone(), idx);
+                self.players.push(Player {
+                    name,
+                    score: 0,
+                    stats: PlayerStats::default(),
+                });
+
+                idx
+            }
+        };
+
+        &mut self.players[idx]
+    }
+
+    /// Renames a player, keeping their score, their stats and
+    /// their position.
+    ///
+    /// If there's no player named `old`, or if there's already a
+    /// player named `new`, `new` is simply inserted instead.
+    pub fn rename(&mut self, old: &str, new: &str) {
+        if self.indices.contains_key(new) {
+            return;
+        }
+
+        let Some(idx) = self.indices.remove(old) else {
+            self.get_or_insert(new);
+            return;
+        };
+
+        let name: Rc<str> = Rc::from(new);
+        self.indices.insert(name.clone(), idx);
+        self.players[idx].name = name;
+    }
+
+    /// Returns the player with the given name, if any.
+    #[cfg(test)]
+    pub fn get(&self, name: &str) -> Option<&Player> {
+        let &idx = self.indices.get(name)?;
+
+        self.players.get(idx)
+    }
+
+    /// Iterates over all players, in order of first appearance.
+    pub fn iter(&self) -> impl Iterator<Item = &Player> {
+        self.players.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.players.clear();
+        self.indices.clear();
+    }
+}
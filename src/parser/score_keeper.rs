@@ -0,0 +1,244 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use super::{
+    event::{Event, TimedEvent},
+    handler::{EventHandler, ResolvedKill},
+    players::{Player, Players},
+    CauseOfDeathCounter,
+};
+use crate::{
+    extra_checked_ops::ExtraCheckedOps, strip_color_codes,
+    ClientId, ClientInfo, GameReport, ItemCounter, PlayerOrder,
+    PlayerRegistry, Result, ScoreboardEntry, ScoringRules,
+};
+
+/// The built-in handler, which keeps the players' scores and
+/// stats, the causes of death of every kill, the items picked
+/// up and the server's final scoreboard
+#[derive(Default)]
+pub(crate) struct ScoreKeeper {
+    /// How many kills happened during this game,
+    /// including the ones caused by `<world>`.
+    pub total_kills: u32,
+    /// Totals up how many deaths were caused by each
+    /// cause of death.
+    pub cause_of_death_counter: CauseOfDeathCounter,
+    /// Totals up how many times each item was picked up.
+    pub item_counter: ItemCounter,
+    /// Totals up how many times each item added by a mod was
+    /// picked up, by class name.
+    pub other_items: BTreeMap<String, u32>,
+    /// The final scores logged by the server so far.
+    pub scoreboard: Vec<ScoreboardEntry>,
+    /// Every player seen during this game, along with their
+    /// scores.
+    pub players: Players,
+    /// The order in which players are listed in reports.
+    pub player_order: PlayerOrder,
+    /// How kills affect the players' scores.
+    pub scoring_rules: ScoringRules,
+}
+
+impl EventHandler for ScoreKeeper {
+    fn event(
+        &mut self,
+        event: &TimedEvent,
+        registry: &PlayerRegistry,
+    ) -> Result<()> {
+        match event.event {
+            Event::Item {
+                client_id,
+                item,
+                ref class_name,
+            } => {
+                match item {
+                    Some(item) => self.item_counter.add(item)?,
+                    None => self
+                        .other_items
+                        .entry(class_name.clone())
+                        .or_default()
+                        .checked_increment()?,
+                }
+
+                // Pickups by clients who never told their names
+                // can't be attributed
+                let stats = match registry.get(client_id) {
+                    Some(client) if !client.name.is_empty() => {
+                        &mut self
+                            .players
+                            .get_or_insert(&client.name)
+                            .stats
+                    }
+                    _ => return Ok(()),
+                };
+
+                match item {
+                    Some(item) => stats.items.add(item),
+                    None => stats
+                        .other_items
+                        .entry(class_name.clone())
+                        .or_default()
+                        .checked_increment(),
+                }
+            }
+            Event::Score {
+                score,
+                ping,
+                client_id,
+                ref name,
+            } => {
+                // Players are known by their registered names,
+                // which may differ from the logged one if they
+                // changed it mid-game
+                let name = match registry.get(client_id) {
+                    Some(client) if !client.name.is_empty() => {
+                        client.name.clone()
+                    }
+                    _ => strip_color_codes(name),
+                };
+
+                self.scoreboard.push(ScoreboardEntry {
+                    score,
+                    ping,
+                    client_id,
+                    name,
+                });
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn kill(&mut self, kill: &ResolvedKill) -> Result<()> {
+        let cause_of_death = kill.kill.cause_of_death;
+        let rules = self.scoring_rules;
+
+        self.cause_of_death_counter.add(cause_of_death)?;
+
+        // Players are listed in order of first appearance, so
+        // the attacker is registered before their victim
+        if !kill.by_world() {
+            self.players.get_or_insert(kill.attacker);
+        }
+
+        let victim = self.players.get_or_insert(kill.victim);
+        victim.stats.deaths.checked_increment()?;
+        victim.stats.deaths_by_means.add(cause_of_death)?;
+
+        if kill.by_world() {
+            victim
+                .score
+                .checked_sub_assign(rules.world_penalty)?;
+            victim.stats.world_deaths.checked_increment()?;
+        } else if kill.self_kill() {
+            // Killing oneself is no kill
+            victim
+                .score
+                .checked_sub_assign(rules.suicide_penalty)?;
+            victim.stats.self_kills.checked_increment()?;
+        } else if kill.team_kill {
+            let attacker =
+                self.players.get_or_insert(kill.attacker);
+            attacker
+                .score
+                .checked_sub_assign(rules.team_kill_penalty)?;
+            attacker.stats.team_kills.checked_increment()?;
+        } else {
+            let attacker =
+                self.players.get_or_insert(kill.attacker);
+            attacker
+                .score
+                .checked_add_assign(rules.kill_points)?;
+            attacker.stats.kills.checked_increment()?;
+            attacker.stats.kills_by_means.add(cause_of_death)?;
+        }
+
+        self.total_kills.checked_increment()?;
+
+        Ok(())
+    }
+
+    fn userinfo_changed(
+        &mut self,
+        _client_id: ClientId,
+        info: &ClientInfo,
+        previous: Option<&ClientInfo>,
+    ) -> Result<()> {
+        let name = &info.name;
+
+        if name.is_empty() {
+            // Not a player we can report on
+            return Ok(());
+        }
+
+        match previous {
+            // The player changed their name mid-game, so their
+            // stats should follow them
+            Some(previous)
+                if !previous.name.is_empty()
+                    && previous.name != *name =>
+            {
+                self.players.rename(&previous.name, name)
+            }
+            _ => {
+                self.players.get_or_insert(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn game_end(&mut self, report: &mut GameReport) {
+        let mut players: Vec<&Player> =
+            self.players.iter().collect();
+
+        // Stable sorts, so ties remain in order of first
+        // appearance
+        match self.player_order {
+            PlayerOrder::FirstAppearance => {}
+            PlayerOrder::Score => players
+                .sort_by_key(|player| Reverse(player.score)),
+            PlayerOrder::Name => {
+                players.sort_by(|a, b| a.name.cmp(&b.name))
+            }
+        }
+
+        report.total_kills = self.total_kills;
+        report.players = players
+            .iter()
+            .map(|player| player.name.to_string())
+            .collect();
+        report.kills = players
+            .iter()
+            .map(|player| {
+                (player.name.to_string(), player.score)
+            })
+            .collect();
+        report.stats = players
+            .iter()
+            .map(|player| {
+                (player.name.to_string(), player.stats.clone())
+            })
+            .collect();
+        report.kills_by_means =
+            std::mem::take(&mut self.cause_of_death_counter);
+        // Neither can add up to more than `total_kills`
+        report.kills_by_weapon = report
+            .kills_by_means
+            .by_weapon()
+            .expect("kills by weapon never overflow");
+        report.deaths_by_category = report
+            .kills_by_means
+            .by_category()
+            .expect("deaths by category never overflow");
+        report.items = std::mem::take(&mut self.item_counter);
+        report.other_items =
+            std::mem::take(&mut self.other_items);
+        report.scoreboard = std::mem::take(&mut self.scoreboard);
+
+        // Get ready for the next game
+        self.players.clear();
+        self.total_kills = 0;
+    }
+}
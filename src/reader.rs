@@ -0,0 +1,265 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Cursor, Read},
+    path::Path,
+};
+
+use flate2::bufread::MultiGzDecoder;
+
+use crate::Result;
+
+/// The first bytes of a gzip member.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The first bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// A line reader that reuses the same buffer for every line
+/// it reads.
+///
+/// Input compressed with gzip or zstd, such as rotated logs, is
+/// detected by its magic bytes and decompressed on the fly.
+/// Lines that aren't valid UTF-8 are read as Latin-1, see
+/// [`decode_line`].
+pub struct ReallocBufReader {
+    reader: Box<dyn BufRead>,
+    /// The line being read, as raw bytes.
+    bytes: Vec<u8>,
+    buffer: String,
+}
+
+impl ReallocBufReader {
+    /// Reads lines out of the given reader, decompressing them
+    /// if needed.
+    pub fn new(reader: impl BufRead + 'static) -> Result<Self> {
+        let reader = decompress(reader)?;
+        let bytes = Vec::with_capacity(1024);
+        let buffer = String::with_capacity(1024);
+
+        Ok(Self {
+            reader,
+            bytes,
+            buffer,
+        })
+    }
+
+    /// Opens the file at the given path for reading.
+    pub fn from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+
+        Self::new(BufReader::new(file))
+    }
+
+    /// Reads lines out of the standard input.
+    pub fn stdin() -> Result<Self> {
+        Self::new(io::stdin().lock())
+    }
+
+    /// Reads the next line, returning `None` once the end of the
+    /// file is reached.
+    ///
+    /// The returned line is only valid until the next call to
+    /// this function.
+    pub fn read_line(&mut self) -> Result<Option<LogLine<'_>>> {
+        self.bytes.clear();
+
+        let bytes_read =
+            self.reader.read_until(b'\n', &mut self.bytes)?;
+        let line = decode_line(&self.bytes, &mut self.buffer);
+
+        Ok((bytes_read != 0).then_some(line))
+    }
+}
+
+/// A line of a log, decoded into a string
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LogLine<'a> {
+    /// The decoded line, along with its line terminator.
+    pub text: &'a str,
+    /// How many bytes the line takes up in the log, which is
+    /// less than `text` does if it was decoded from Latin-1.
+    pub raw_len: usize,
+}
+
+impl LogLine<'_> {
+    /// Maps a byte position within `text` back to the bytes of
+    /// the line as found in the log.
+    pub fn raw_position(&self, position: usize) -> usize {
+        if self.raw_len == self.text.len() {
+            position
+        } else {
+            // Decoded from Latin-1, one character per byte
+            self.text[..position].chars().count()
+        }
+    }
+}
+
+/// Lines that didn't go through a reader are taken as is.
+impl<'a> From<&'a str> for LogLine<'a> {
+    fn from(text: &'a str) -> Self {
+        Self {
+            text,
+            raw_len: text.len(),
+        }
+    }
+}
+
+impl<'a> From<&'a String> for LogLine<'a> {
+    fn from(text: &'a String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+/// Decodes a line of a log into `buffer`.
+///
+/// Servers log names byte for byte, and players are fond of the
+/// high-ASCII characters of the game's font, so lines that
+/// aren't valid UTF-8 are decoded as Latin-1 instead, which maps
+/// every byte to a character.
+pub(crate) fn decode_line<'a>(
+    bytes: &[u8],
+    buffer: &'a mut String,
+) -> LogLine<'a> {
+    buffer.clear();
+
+    match std::str::from_utf8(bytes) {
+        Ok(line) => buffer.push_str(line),
+        Err(_) => {
+            buffer.extend(bytes.iter().map(|&b| char::from(b)))
+        }
+    }
+
+    LogLine {
+        text: buffer,
+        raw_len: bytes.len(),
+    }
+}
+
+/// Wraps the given reader in a decoder if its input starts with
+/// the magic bytes of a known compression format.
+fn decompress(
+    mut reader: impl BufRead + 'static,
+) -> Result<Box<dyn BufRead>> {
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut reader)
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+
+    // Put the magic bytes back in front of the rest of the input
+    let reader = Cursor::new(magic).chain(reader);
+    let magic = reader.get_ref().0.get_ref();
+
+    let reader: Box<dyn BufRead> =
+        if magic.starts_with(&GZIP_MAGIC) {
+            // Rotated logs may be made of several gzip members
+            Box::new(BufReader::new(MultiGzDecoder::new(reader)))
+        } else if magic.starts_with(&ZSTD_MAGIC) {
+            Box::new(BufReader::new(zstd::Decoder::with_buffer(
+                reader,
+            )?))
+        } else {
+            Box::new(reader)
+        };
+
+    Ok(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::{LogLine, ReallocBufReader};
+
+    const INPUT: &str = concat!(
+        "  0:00 InitGame: \\mapname\\q3dm17\n",
+        "  1:08 Kill: 3 2 6: Isgalamido killed Mocinha by MOD_ROCKET\n",
+        "  1:47 ShutdownGame:\n",
+    );
+
+    fn read_lines(input: Vec<u8>) -> Vec<String> {
+        let mut reader =
+            ReallocBufReader::new(Cursor::new(input)).unwrap();
+        let mut lines = Vec::new();
+
+        while let Some(line) = reader.read_line().unwrap() {
+            lines.push(line.text.to_owned());
+        }
+
+        lines
+    }
+
+    fn gzip(input: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(input).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn reads_plain_input() {
+        assert_eq!(read_lines(INPUT.into()).concat(), INPUT);
+        assert!(read_lines(Vec::new()).is_empty());
+        assert_eq!(read_lines(b"\x1f".to_vec()), ["\x1f"]);
+    }
+
+    #[test]
+    fn reads_latin1_input() {
+        let input = b"  0:01 Kill: 2 3 7: Z\xe9 killed Mal by MOD_ROCKET\n";
+
+        assert_eq!(
+            read_lines(input.to_vec()),
+            ["  0:01 Kill: 2 3 7: Z\u{e9} killed Mal by MOD_ROCKET\n"]
+        );
+    }
+
+    #[test]
+    fn maps_positions_back_to_raw_bytes() {
+        let mut reader = ReallocBufReader::new(Cursor::new(
+            b"Z\xe9 killed Mal\nZ\xc3\xa9 killed Mal\n".to_vec(),
+        ))
+        .unwrap();
+
+        // Decoded from Latin-1, where every byte is a character
+        let line = reader.read_line().unwrap().unwrap();
+        assert_eq!(line.raw_len, 14);
+        assert_eq!(
+            line.raw_position(line.text.find("killed").unwrap()),
+            3
+        );
+
+        // Valid UTF-8, taken byte for byte
+        let line = reader.read_line().unwrap().unwrap();
+        assert_eq!(line.raw_len, 15);
+        assert_eq!(
+            line.raw_position(line.text.find("killed").unwrap()),
+            4
+        );
+
+        let line = LogLine::from("Zeh killed Mal");
+        assert_eq!(line.raw_position(4), 4);
+    }
+
+    #[test]
+    fn decompresses_gzip() {
+        assert_eq!(
+            read_lines(gzip(INPUT.as_bytes())).concat(),
+            INPUT
+        );
+
+        // As produced by `cat games.log.1.gz games.log.2.gz`
+        let (first, second) = INPUT.split_at(INPUT.len() / 2);
+        let input =
+            [gzip(first.as_bytes()), gzip(second.as_bytes())]
+                .concat();
+        assert_eq!(read_lines(input).concat(), INPUT);
+    }
+
+    #[test]
+    fn decompresses_zstd() {
+        let input =
+            zstd::encode_all(INPUT.as_bytes(), 0).unwrap();
+
+        assert_eq!(read_lines(input).concat(), INPUT);
+    }
+}
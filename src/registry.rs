@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use crate::{settings::atoi, Error, Result};
+
+/// The slot a client occupies in the server, as logged in lines
+/// such as `ClientConnect: 2`
+pub type ClientId = u32;
+
+/// The team a client is playing on
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Team {
+    /// Not on a team, as in free-for-all games.
+    #[default]
+    Free,
+    Red,
+    Blue,
+    Spectator,
+}
+
+impl TryFrom<u8> for Team {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        let team = match value {
+            0 => Team::Free,
+            1 => Team::Red,
+            2 => Team::Blue,
+            3 => Team::Spectator,
+            _ => {
+                return Err(Error::InvalidInfoValue {
+                    key: "t",
+                    value: value.to_string(),
+                })
+            }
+        };
+
+        Ok(team)
+    }
+}
+
+/// The settings of a client, as given by its userinfo, e.g.
+/// `n\Isgalamido\t\0\model\xian/default\c1\4\c2\5\hc\100`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientInfo {
+    /// The player's name (`n`), without color codes.
+    pub name: String,
+    /// The player's name (`n`), as logged.
+    pub raw_name: String,
+    /// The team the player is on (`t`), or `Team::Free` if it
+    /// isn't a valid team.
+    pub team: Team,
+    /// The player's model (`model`).
+    pub model: String,
+    /// The player's head model (`hmodel`).
+    pub head_model: String,
+    /// The player's handicap, from 1 to 100 (`hc`), or 100 if
+    /// it's out of range.
+    pub handicap: u32,
+    /// The player's primary color (`c1`).
+    pub color1: String,
+    /// The player's secondary color (`c2`).
+    pub color2: String,
+    /// Every key and value of the userinfo, including the ones
+    /// above.
+    pub userinfo: BTreeMap<String, String>,
+}
+
+impl ClientInfo {
+    /// Builds a `ClientInfo` out of the keys and values of a
+    /// userinfo string.
+    pub fn from_userinfo<'a>(
+        userinfo: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let userinfo: BTreeMap<String, String> = userinfo
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+
+        let get = |key: &str| {
+            userinfo.get(key).cloned().unwrap_or_default()
+        };
+
+        // Numbers are read the way the server reads them, and
+        // values out of range leave the defaults in place
+        let team = u8::try_from(atoi(&get("t")))
+            .ok()
+            .and_then(|team| Team::try_from(team).ok())
+            .unwrap_or_default();
+
+        let handicap = match atoi(&get("hc")) {
+            handicap @ 1..=100 => handicap,
+            _ => 100,
+        };
+
+        let raw_name = get("n");
+
+        Self {
+            name: strip_color_codes(&raw_name),
+            raw_name,
+            team,
+            model: get("model"),
+            head_model: get("hmodel"),
+            handicap,
+            color1: get("c1"),
+            color2: get("c2"),
+            userinfo,
+        }
+    }
+}
+
+/// Strips the color codes out of a name, such as the `^1` in
+/// `^1Zeh`, leaving the name as it's displayed in the game.
+pub fn strip_color_codes(name: &str) -> String {
+    let mut stripped = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        // The game takes a caret followed by a letter or digit
+        // as a color code
+        match chars.peek() {
+            Some(next)
+                if ch == '^' && next.is_ascii_alphanumeric() =>
+            {
+                chars.next();
+            }
+            _ => stripped.push(ch),
+        }
+    }
+
+    stripped
+}
+
+/// Maps the client slots in use during a game to the settings
+/// of the client occupying them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlayerRegistry {
+    clients: BTreeMap<ClientId, ClientInfo>,
+}
+
+impl PlayerRegistry {
+    /// Returns the settings of the client in the given slot.
+    pub fn get(
+        &self,
+        client_id: ClientId,
+    ) -> Option<&ClientInfo> {
+        self.clients.get(&client_id)
+    }
+
+    /// Iterates over every client, in order of their ids.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (ClientId, &ClientInfo)> {
+        self.clients.iter().map(|(&id, info)| (id, info))
+    }
+
+    /// Updates the settings of the client in the given slot,
+    /// returning their previous settings.
+    pub(crate) fn insert(
+        &mut self,
+        client_id: ClientId,
+        info: ClientInfo,
+    ) -> Option<ClientInfo> {
+        self.clients.insert(client_id, info)
+    }
+
+    /// Frees up the given slot.
+    pub(crate) fn remove(&mut self, client_id: ClientId) {
+        self.clients.remove(&client_id);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.clients.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_color_codes, ClientInfo, Team};
+
+    #[test]
+    fn builds_client_info_from_userinfo() {
+        let info = ClientInfo::from_userinfo([
+            ("n", "Isgalamido"),
+            ("t", "2"),
+            ("model", "xian/default"),
+            ("hmodel", "xian/default"),
+            ("g_redteam", ""),
+            ("c1", "4"),
+            ("c2", "5"),
+            ("hc", "70"),
+        ]);
+
+        assert_eq!(info.name, "Isgalamido");
+        assert_eq!(info.team, Team::Blue);
+        assert_eq!(info.model, "xian/default");
+        assert_eq!(info.head_model, "xian/default");
+        assert_eq!(info.handicap, 70);
+        assert_eq!(info.color1, "4");
+        assert_eq!(info.color2, "5");
+        assert_eq!(info.userinfo["g_redteam"], "");
+    }
+
+    #[test]
+    fn strips_color_codes() {
+        assert_eq!(strip_color_codes("^1Zeh"), "Zeh");
+        assert_eq!(
+            strip_color_codes("^4Dono ^7da ^xBola"),
+            "Dono da Bola"
+        );
+        assert_eq!(strip_color_codes("^^1Mal^"), "^Mal^");
+        assert_eq!(
+            strip_color_codes("Isgalamido"),
+            "Isgalamido"
+        );
+
+        let info = ClientInfo::from_userinfo([("n", "^1Z^7eh")]);
+        assert_eq!(info.name, "Zeh");
+        assert_eq!(info.raw_name, "^1Z^7eh");
+    }
+
+    #[test]
+    fn falls_back_on_invalid_numbers() {
+        for (team, handicap) in
+            [("7", "0"), ("red", ""), ("256", "101")]
+        {
+            let info = ClientInfo::from_userinfo([
+                ("t", team),
+                ("hc", handicap),
+            ]);
+
+            assert_eq!(info.team, Team::Free);
+            assert_eq!(info.handicap, 100);
+            assert_eq!(info.userinfo["t"], team);
+            assert_eq!(info.userinfo["hc"], handicap);
+        }
+
+        let info = ClientInfo::from_userinfo([
+            ("t", " 1st"),
+            ("hc", "50%"),
+        ]);
+        assert_eq!(info.team, Team::Red);
+        assert_eq!(info.handicap, 50);
+    }
+}
@@ -0,0 +1,312 @@
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+use serde::{
+    ser::{Error as _, SerializeMap, SerializeStruct},
+    Serialize, Serializer,
+};
+
+use crate::{
+    CauseOfDeathCounter, ClientId, DeathCategoryCounter, Error,
+    GameSettings, InstanceCounter, ItemCounter, Result,
+    WeaponCounter,
+};
+
+/// The order in which players are listed in a report
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PlayerOrder {
+    /// In the order they first appeared in the game.
+    #[default]
+    FirstAppearance,
+    /// From the highest score to the lowest.
+    Score,
+    /// Alphabetically, by name.
+    Name,
+}
+
+impl FromStr for PlayerOrder {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "first-seen" => Ok(Self::FirstAppearance),
+            "score" => Ok(Self::Score),
+            "name" => Ok(Self::Name),
+            _ => Err(Error::InvalidArgument(format!(
+                "unknown player order `{input}`"
+            ))),
+        }
+    }
+}
+
+/// A summary of a single Quake 3 Arena game
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GameReport {
+    /// The index of this game within the log.
+    ///
+    /// Not serialized as part of the report: it is used as the
+    /// report's key instead (e.g. `"game0"`).
+    #[serde(skip)]
+    pub game_idx: u32,
+    /// How many kills happened during this game,
+    /// including the ones caused by `<world>`.
+    pub total_kills: u32,
+    /// Every player seen during this game, whether they scored
+    /// or not.
+    pub players: Vec<String>,
+    /// Every player's score, listed in the same order as
+    /// `players`.
+    #[serde(serialize_with = "serialize_as_map")]
+    pub kills: Vec<(String, i32)>,
+    /// A breakdown of every player's kills and deaths, listed
+    /// in the same order as `players`.
+    #[serde(serialize_with = "serialize_as_map")]
+    pub stats: Vec<(String, PlayerStats)>,
+    /// Totals up how many deaths were caused by each
+    /// cause of death.
+    pub kills_by_means: CauseOfDeathCounter,
+    /// `kills_by_means` rolled up by weapon, leaving out the
+    /// deaths no weapon caused.
+    pub kills_by_weapon: WeaponCounter,
+    /// `kills_by_means` rolled up by category, such as the
+    /// deaths caused by the environment.
+    pub deaths_by_category: DeathCategoryCounter,
+    /// Totals up how many times each item was picked up,
+    /// leaving out the ones that never were.
+    #[serde(serialize_with = "serialize_non_zero")]
+    pub items: ItemCounter,
+    /// Totals up how many times each item that isn't one of
+    /// [`Item`](crate::Item)'s was picked up, by class name.
+    ///
+    /// Left out of the serialized report when empty.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub other_items: BTreeMap<String, u32>,
+    /// The final scores as logged by the server once the game
+    /// ended, in the order they were logged.
+    ///
+    /// Empty if the game ended without them, e.g. because it
+    /// was shut down by hand.
+    pub scoreboard: Vec<ScoreboardEntry>,
+    /// The server settings this game was started with, if its
+    /// `InitGame` line was seen.
+    pub settings: Option<GameSettings>,
+    /// How long the game lasted, from its `InitGame` line to
+    /// its `ShutdownGame` line (or its last line, if it was
+    /// never shut down).
+    ///
+    /// Serialized in seconds, and missing if the `InitGame`
+    /// line wasn't seen.
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub duration: Option<Duration>,
+    /// Whether the game ended without a `ShutdownGame` line,
+    /// e.g. because the server crashed.
+    pub incomplete: bool,
+    /// The log this game started in, if it was named through
+    /// [`LogParser::set_source`](crate::LogParser::set_source).
+    pub source: Option<String>,
+    /// Statistics added by custom
+    /// [`EventHandler`](crate::EventHandler)s, by name.
+    ///
+    /// Left out of the serialized report when empty.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, serde_json::Value>,
+}
+
+/// A player's final score, as logged by the server through a
+/// line such as `score: 20  ping: 4  client: 4 Zeh`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ScoreboardEntry {
+    /// The score reckoned by the server, which may include more
+    /// than kills, such as flag captures in CTF games.
+    pub score: i32,
+    pub ping: u32,
+    pub client_id: ClientId,
+    /// The player's name, without color codes.
+    pub name: String,
+}
+
+impl GameReport {
+    /// The key this report is written under, e.g. `"game0"`.
+    pub fn key(&self) -> String {
+        format!("game{}", self.game_idx)
+    }
+
+    /// Returns the score of the given player, if they are part
+    /// of this game.
+    pub fn score_of(&self, player: &str) -> Option<i32> {
+        self.kills
+            .iter()
+            .find(|(name, _)| name == player)
+            .map(|&(_, score)| score)
+    }
+
+    /// Returns the stats of the given player, if they are part
+    /// of this game.
+    pub fn stats_of(
+        &self,
+        player: &str,
+    ) -> Option<&PlayerStats> {
+        self.stats
+            .iter()
+            .find(|(name, _)| name == player)
+            .map(|(_, stats)| stats)
+    }
+}
+
+/// How a player fared during a game
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    /// How many other players they killed.
+    pub kills: u32,
+    /// How many times they died, however it happened.
+    pub deaths: u32,
+    /// How many of their deaths were caused by `<world>`, such
+    /// as falling or drowning.
+    pub world_deaths: u32,
+    /// How many times they killed themselves, such as with
+    /// their own rocket.
+    pub self_kills: u32,
+    /// How many teammates they killed, which don't count
+    /// towards `kills`.
+    pub team_kills: u32,
+    /// The causes of death of the players they killed, adding
+    /// up to `kills`.
+    pub kills_by_means: CauseOfDeathCounter,
+    /// The causes of their deaths, adding up to `deaths`.
+    pub deaths_by_means: CauseOfDeathCounter,
+    /// How many times they picked up each item.
+    pub items: ItemCounter,
+    /// How many times they picked up each item that isn't one
+    /// of [`Item`](crate::Item)'s, by class name.
+    pub other_items: BTreeMap<String, u32>,
+}
+
+impl PlayerStats {
+    /// Kills per death. Players who never died get their kills
+    /// as their ratio.
+    pub fn kd_ratio(&self) -> f64 {
+        f64::from(self.kills) / f64::from(self.deaths.max(1))
+    }
+}
+
+/// Serializes the stats along with their K/D ratio, and their
+/// kills and deaths rolled up by weapon and by category. Causes
+/// of death, weapons, categories and items that never came up
+/// are left out of the per-player maps, and so are
+/// `other_items` when none were picked up.
+impl Serialize for PlayerStats {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut stats =
+            serializer.serialize_struct("PlayerStats", 12)?;
+
+        stats.serialize_field("kills", &self.kills)?;
+        stats.serialize_field("deaths", &self.deaths)?;
+        stats.serialize_field(
+            "world_deaths",
+            &self.world_deaths,
+        )?;
+        stats.serialize_field("self_kills", &self.self_kills)?;
+        stats.serialize_field("team_kills", &self.team_kills)?;
+        stats.serialize_field("kd_ratio", &self.kd_ratio())?;
+        stats.serialize_field(
+            "kills_by_means",
+            &NonZero(&self.kills_by_means),
+        )?;
+        stats.serialize_field(
+            "deaths_by_means",
+            &NonZero(&self.deaths_by_means),
+        )?;
+        stats.serialize_field(
+            "kills_by_weapon",
+            &NonZero(
+                &self
+                    .kills_by_means
+                    .by_weapon()
+                    .map_err(S::Error::custom)?,
+            ),
+        )?;
+        stats.serialize_field(
+            "deaths_by_category",
+            &NonZero(
+                &self
+                    .deaths_by_means
+                    .by_category()
+                    .map_err(S::Error::custom)?,
+            ),
+        )?;
+        stats.serialize_field("items", &NonZero(&self.items))?;
+        if self.other_items.is_empty() {
+            stats.skip_field("other_items")?;
+        } else {
+            stats.serialize_field(
+                "other_items",
+                &self.other_items,
+            )?;
+        }
+
+        stats.end()
+    }
+}
+
+/// Serializes a counter as a map of only the elements that were
+/// counted at least once.
+struct NonZero<'a, T: Into<u8>, const N: usize>(
+    &'a InstanceCounter<T, N>,
+);
+
+impl<T, const N: usize> Serialize for NonZero<'_, T, N>
+where
+    T: Into<u8> + TryFrom<u8> + Serialize,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0.iter().filter(|&(_, count)| count > 0),
+        )
+    }
+}
+
+/// Serializes a counter through [`NonZero`].
+fn serialize_non_zero<S, T, const N: usize>(
+    counter: &InstanceCounter<T, N>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Into<u8> + TryFrom<u8> + Serialize,
+{
+    NonZero(counter).serialize(serializer)
+}
+
+/// Serializes a duration as a number of seconds.
+fn serialize_as_secs<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    duration
+        .map(|duration| duration.as_secs())
+        .serialize(serializer)
+}
+
+/// Serializes a list of pairs as a map, keeping their order.
+fn serialize_as_map<S, K, V>(
+    pairs: &[(K, V)],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Serialize,
+    V: Serialize,
+{
+    let mut map = serializer.serialize_map(Some(pairs.len()))?;
+
+    for (key, value) in pairs {
+        map.serialize_entry(key, value)?;
+    }
+
+    map.end()
+}
@@ -0,0 +1,29 @@
+/// How kills affect the players' scores
+///
+/// Defaults to the rules of the game itself: a point for every
+/// kill, and a point off for dying to `<world>`, killing oneself
+/// or killing a teammate.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScoringRules {
+    /// Points earned for killing another player.
+    pub kill_points: i32,
+    /// Points taken from a player killed by `<world>`, such as
+    /// by falling or drowning.
+    pub world_penalty: i32,
+    /// Points taken from a player who killed themselves, such
+    /// as with their own rocket.
+    pub suicide_penalty: i32,
+    /// Points taken from a player who killed a teammate.
+    pub team_kill_penalty: i32,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self {
+            kill_points: 1,
+            world_penalty: 1,
+            suicide_penalty: 1,
+            team_kill_penalty: 1,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// The game modes of Quake 3 Arena, as set by `g_gametype`
+#[derive(
+    Clone, Copy, PartialEq, Eq, Debug, Default, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum GameType {
+    /// Free for all (`0`).
+    #[default]
+    FreeForAll,
+    /// One on one tournament (`1`).
+    Tournament,
+    /// Single player (`2`).
+    SinglePlayer,
+    /// Team deathmatch (`3`).
+    TeamDeathmatch,
+    /// Capture the flag (`4`).
+    CaptureTheFlag,
+    /// Any other game mode, such as the ones from Team Arena.
+    Other(u32),
+}
+
+impl GameType {
+    /// Whether players only score by killing, as opposed to game
+    /// modes that also award points for objectives, such as
+    /// capturing flags.
+    pub fn scores_by_kills(self) -> bool {
+        !matches!(
+            self,
+            GameType::CaptureTheFlag | GameType::Other(_)
+        )
+    }
+}
+
+impl From<u32> for GameType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => GameType::FreeForAll,
+            1 => GameType::Tournament,
+            2 => GameType::SinglePlayer,
+            3 => GameType::TeamDeathmatch,
+            4 => GameType::CaptureTheFlag,
+            other => GameType::Other(other),
+        }
+    }
+}
+
+/// The server settings a game was started with, as logged by
+/// `InitGame`, e.g.
+/// `\mapname\q3dm17\g_gametype\0\fraglimit\20\timelimit\15`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GameSettings {
+    /// The map being played (`mapname`).
+    pub map_name: String,
+    /// The game mode (`g_gametype`).
+    pub game_type: GameType,
+    /// How many frags end the game (`fraglimit`).
+    pub frag_limit: Option<u32>,
+    /// How many minutes the game lasts (`timelimit`).
+    pub time_limit: Option<u32>,
+    /// How many captures end the game (`capturelimit`).
+    pub capture_limit: Option<u32>,
+    /// The name of the server (`sv_hostname`).
+    pub hostname: String,
+    /// The version of the server (`version`).
+    pub version: String,
+    /// Every other cvar logged, along with its value.
+    pub cvars: BTreeMap<String, String>,
+}
+
+impl GameSettings {
+    /// Builds a `GameSettings` out of the cvars logged by
+    /// `InitGame`.
+    pub fn from_cvars<'a>(
+        cvars: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let mut settings = Self::default();
+
+        for (key, value) in cvars {
+            match key {
+                "mapname" => settings.map_name = value.into(),
+                "g_gametype" => {
+                    settings.game_type = atoi(value).into()
+                }
+                "fraglimit" => {
+                    settings.frag_limit = Some(atoi(value))
+                }
+                "timelimit" => {
+                    settings.time_limit = Some(atoi(value))
+                }
+                "capturelimit" => {
+                    settings.capture_limit = Some(atoi(value))
+                }
+                "sv_hostname" => {
+                    settings.hostname = value.into()
+                }
+                "version" => settings.version = value.into(),
+                _ => {
+                    settings
+                        .cvars
+                        .insert(key.into(), value.into());
+                }
+            }
+        }
+
+        settings
+    }
+}
+
+/// Reads a numeric cvar the same way the server does, through
+/// `atoi`: leading whitespace is skipped and reading stops at
+/// the first non-digit, so malformed values (e.g. `= 0`, which
+/// happens when an admin types `g_gametype = 0`) count as zero.
+pub(crate) fn atoi(value: &str) -> u32 {
+    value
+        .trim_start()
+        .trim_start_matches('+')
+        .bytes()
+        .take_while(u8::is_ascii_digit)
+        .fold(0_u32, |number, digit| {
+            number
+                .saturating_mul(10)
+                .saturating_add(u32::from(digit - b'0'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameSettings, GameType};
+
+    #[test]
+    fn builds_settings_from_cvars() {
+        let settings = GameSettings::from_cvars([
+            ("sv_floodProtect", "1"),
+            ("sv_hostname", "Code Miner Server"),
+            ("g_gametype", "4"),
+            ("fraglimit", "20"),
+            ("timelimit", "15"),
+            ("capturelimit", "8"),
+            ("version", "ioq3 1.36 linux-x86_64 Apr 12 2009"),
+            ("mapname", "q3dm17"),
+        ]);
+
+        assert_eq!(settings.map_name, "q3dm17");
+        assert_eq!(settings.game_type, GameType::CaptureTheFlag);
+        assert!(!settings.game_type.scores_by_kills());
+        assert_eq!(settings.frag_limit, Some(20));
+        assert_eq!(settings.time_limit, Some(15));
+        assert_eq!(settings.capture_limit, Some(8));
+        assert_eq!(settings.hostname, "Code Miner Server");
+        assert_eq!(
+            settings.version,
+            "ioq3 1.36 linux-x86_64 Apr 12 2009"
+        );
+        assert_eq!(settings.cvars.len(), 1);
+        assert_eq!(settings.cvars["sv_floodProtect"], "1");
+    }
+
+    #[test]
+    fn reads_numbers_like_the_server() {
+        let settings = GameSettings::from_cvars([
+            ("g_gametype", "= 3"),
+            ("fraglimit", "lots"),
+            ("timelimit", " 20min"),
+        ]);
+
+        assert_eq!(settings.game_type, GameType::FreeForAll);
+        assert_eq!(settings.frag_limit, Some(0));
+        assert_eq!(settings.time_limit, Some(20));
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+/// Something unexpected found in a log, which did not prevent
+/// it from being parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// The names in a kill message don't match the names of the
+    /// clients whose ids were logged alongside it.
+    ///
+    /// The kill is attributed according to the client ids.
+    KillMismatch {
+        /// The kill message, as logged.
+        logged: String,
+        /// The kill message, as expected from the client ids.
+        expected: String,
+    },
+    /// A player's score, as computed from the kills of a game,
+    /// doesn't match the one the server logged once it ended.
+    ///
+    /// Only checked when following the game's own scoring
+    /// rules, in game modes where players only score by
+    /// killing.
+    ScoreMismatch {
+        /// The key of the game's report, e.g. `"game0"`.
+        game: String,
+        /// The player's name.
+        player: String,
+        /// The score computed from the kills of the game.
+        computed: i32,
+        /// The score logged by the server.
+        logged: i32,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Warning::KillMismatch { logged, expected } => write!(
+                f,
+                "kill message `{logged}` does not match the \
+                 client ids logged with it, expected `{expected}`"
+            ),
+            Warning::ScoreMismatch {
+                game,
+                player,
+                computed,
+                logged,
+            } => write!(
+                f,
+                "{game}: `{player}` scored {computed} according \
+                 to their kills, but the server logged {logged}"
+            ),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Display;
+
+use serde::{Serialize, Serializer};
+
+/// How many weapons there are.
+pub const WEAPONS: usize = 13;
+
+#[cfg(test)]
+use strum::EnumCount;
+
+use crate::{Error, InstanceCounter};
+#[cfg(test)]
+// Ensure `WEAPONS` is correct
+static_assertions::const_assert_eq!(WEAPONS, Weapon::COUNT);
+
+/// Counts how many kills were made with each weapon.
+pub type WeaponCounter = InstanceCounter<Weapon, WEAPONS>;
+
+/// The weapons of Q3A and its Team Arena expansion
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(test, derive(strum::EnumIter, strum::EnumCount))]
+#[repr(u8)]
+pub enum Weapon {
+    Gauntlet,
+    Machinegun,
+    Shotgun,
+    GrenadeLauncher,
+    RocketLauncher,
+    LightningGun,
+    Railgun,
+    PlasmaGun,
+    Bfg,
+    GrapplingHook,
+    Nailgun,
+    ProxLauncher,
+    Chaingun,
+}
+
+impl Weapon {
+    /// Converts a weapon into its Q3A name
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Weapon::Gauntlet => "WP_GAUNTLET",
+            Weapon::Machinegun => "WP_MACHINEGUN",
+            Weapon::Shotgun => "WP_SHOTGUN",
+            Weapon::GrenadeLauncher => "WP_GRENADE_LAUNCHER",
+            Weapon::RocketLauncher => "WP_ROCKET_LAUNCHER",
+            Weapon::LightningGun => "WP_LIGHTNING_GUN",
+            Weapon::Railgun => "WP_RAILGUN",
+            Weapon::PlasmaGun => "WP_PLASMA_GUN",
+            Weapon::Bfg => "WP_BFG",
+            Weapon::GrapplingHook => "WP_GRAPPLING_HOOK",
+            Weapon::Nailgun => "WP_NAILGUN",
+            Weapon::ProxLauncher => "WP_PROX_LAUNCHER",
+            Weapon::Chaingun => "WP_CHAINGUN",
+        }
+    }
+}
+
+impl From<Weapon> for u8 {
+    fn from(weapon: Weapon) -> Self {
+        weapon as Self
+    }
+}
+
+impl TryFrom<u8> for Weapon {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let weapon = match value {
+            0 => Weapon::Gauntlet,
+            1 => Weapon::Machinegun,
+            2 => Weapon::Shotgun,
+            3 => Weapon::GrenadeLauncher,
+            4 => Weapon::RocketLauncher,
+            5 => Weapon::LightningGun,
+            6 => Weapon::Railgun,
+            7 => Weapon::PlasmaGun,
+            8 => Weapon::Bfg,
+            9 => Weapon::GrapplingHook,
+            10 => Weapon::Nailgun,
+            11 => Weapon::ProxLauncher,
+            12 => Weapon::Chaingun,
+            _ => return Err(Error::WeaponFromByte(value)),
+        };
+
+        Ok(weapon)
+    }
+}
+
+impl Display for Weapon {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Weapon {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::{EnumCount, IntoEnumIterator};
+
+    use super::Weapon;
+
+    #[test]
+    fn check_u8_round_trip() {
+        for (idx, expected_weapon) in
+            (0..Weapon::COUNT).zip(Weapon::iter())
+        {
+            let weapon = Weapon::try_from(idx as u8).unwrap();
+            let byte: u8 = weapon.into();
+
+            assert_eq!(weapon, expected_weapon);
+            assert_eq!(byte as usize, idx);
+        }
+
+        assert!(Weapon::try_from(Weapon::COUNT as u8).is_err());
+    }
+}
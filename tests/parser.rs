@@ -0,0 +1,531 @@
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+    time::Duration,
+};
+
+use q3a_muncher::{
+    CauseOfDeath, DeathCategory, Error, Event, Events,
+    GameReport, InstanceCounter, LogParser, OutputFormat,
+    ParseMode, PlayerOrder, ReallocBufReader, ReportWriter,
+    TimedEvent, CAUSES_OF_DEATH,
+};
+
+/// Parses all of `sample.log`, returning the parser along with
+/// every game's report
+fn parse_sample_log() -> (LogParser, Vec<GameReport>) {
+    let mut reader =
+        ReallocBufReader::from("sample.log").unwrap();
+    let mut parser = LogParser::new();
+    let mut reports = Vec::new();
+
+    while let Some(line) = reader.read_line().unwrap() {
+        reports.extend(parser.parse_line(line).unwrap());
+    }
+    reports.extend(parser.finish());
+
+    (parser, reports)
+}
+
+#[test]
+fn parses_sample_log() {
+    let (_, reports) = parse_sample_log();
+
+    // One of the 21 games was never shut down
+    assert_eq!(reports.len(), 21);
+    assert_eq!(
+        reports
+            .iter()
+            .filter(|report| report.incomplete)
+            .count(),
+        1
+    );
+    assert!(reports[1].incomplete);
+
+    for (idx, report) in reports.iter().enumerate() {
+        assert_eq!(report.game_idx as usize, idx);
+    }
+
+    // From 0:00 to 20:37, and from 20:37 to the game's last
+    // line, at 26:09
+    assert_eq!(
+        reports[0].duration,
+        Some(Duration::from_secs(20 * 60 + 37))
+    );
+    assert_eq!(
+        reports[1].duration,
+        Some(Duration::from_secs(5 * 60 + 32))
+    );
+}
+
+#[test]
+fn rolls_up_causes_of_death_of_sample_log() {
+    let (_, reports) = parse_sample_log();
+
+    for report in &reports {
+        let categories = &report.deaths_by_category;
+        assert_eq!(
+            categories.total().unwrap(),
+            report.total_kills
+        );
+
+        // Team Arena's weapons count towards both
+        assert!(
+            categories.get(DeathCategory::Weapon).unwrap()
+                <= report.kills_by_weapon.total().unwrap()
+        );
+    }
+
+    let environment: u32 = reports
+        .iter()
+        .filter_map(|report| {
+            report
+                .deaths_by_category
+                .get(DeathCategory::Environment)
+        })
+        .sum();
+    assert!(environment > 0);
+}
+
+#[test]
+fn counts_item_pickups_of_sample_log() {
+    let (_, reports) = parse_sample_log();
+
+    let total: u32 = reports
+        .iter()
+        .flat_map(|report| report.items.iter())
+        .map(|(_, count)| count)
+        .sum();
+    assert_eq!(total, 3613);
+
+    // Every player's pickups are part of their game's totals
+    for report in &reports {
+        for (item, count) in report.items.iter() {
+            let by_players: u32 = report
+                .stats
+                .iter()
+                .filter_map(|(_, stats)| stats.items.get(item))
+                .sum();
+            assert!(by_players <= count);
+        }
+    }
+}
+
+#[test]
+fn reconciles_scores_of_sample_log() {
+    let (mut parser, reports) = parse_sample_log();
+
+    assert_eq!(
+        reports
+            .iter()
+            .map(|report| report.scoreboard.len())
+            .sum::<usize>(),
+        40
+    );
+
+    // Scores only disagree in CTF games, where they aren't
+    // checked
+    assert!(parser.take_warnings().is_empty());
+
+    let scoreboard = &reports[3].scoreboard;
+    assert_eq!(scoreboard.len(), 4);
+    assert_eq!(scoreboard[0].name, "Zeh");
+    assert_eq!(scoreboard[0].score, 20);
+    assert_eq!(reports[3].score_of("Zeh"), Some(20));
+}
+
+#[test]
+fn streams_events_out_of_sample_log() {
+    let file = File::open("sample.log").unwrap();
+    let events: Vec<TimedEvent> =
+        Events::new(BufReader::new(file))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+    assert_eq!(events.len(), 5306);
+
+    let count = |predicate: fn(&Event) -> bool| {
+        events
+            .iter()
+            .filter(|event| predicate(&event.event))
+            .count()
+    };
+    assert_eq!(
+        count(|event| matches!(event, Event::InitGame(_))),
+        21
+    );
+    assert_eq!(
+        count(|event| matches!(event, Event::Item { .. })),
+        3613
+    );
+    assert_eq!(
+        count(|event| matches!(event, Event::Score { .. })),
+        40
+    );
+    assert_eq!(
+        count(|event| matches!(event, Event::TeamScore { .. })),
+        3
+    );
+}
+
+#[test]
+fn reports_games_that_were_not_shut_down() {
+    let mut parser = LogParser::new();
+
+    let lines = [
+        r"  0:00 InitGame: \mapname\q3dm17",
+        "  1:08 Kill: 3 2 6: Isgalamido killed Mocinha by MOD_ROCKET",
+    ];
+
+    for line in lines {
+        assert_eq!(parser.parse_line(line).unwrap(), None);
+    }
+
+    // A new game starts before the previous one was shut down
+    let report = parser
+        .parse_line(r"  0:00 InitGame: \mapname\q3dm6")
+        .unwrap()
+        .unwrap();
+
+    assert!(report.incomplete);
+    assert_eq!(report.game_idx, 0);
+    assert_eq!(report.total_kills, 1);
+    assert_eq!(
+        report.settings.as_ref().unwrap().map_name,
+        "q3dm17"
+    );
+
+    parser
+        .parse_line("  0:15 Kill: 2 3 6: Zeh killed Mocinha by MOD_ROCKET")
+        .unwrap();
+
+    // The log ends in the middle of the second game
+    let report = parser.finish().unwrap();
+
+    assert!(report.incomplete);
+    assert_eq!(report.game_idx, 1);
+    assert_eq!(report.total_kills, 1);
+    assert_eq!(report.score_of("Zeh"), Some(1));
+    assert_eq!(
+        report.settings.as_ref().unwrap().map_name,
+        "q3dm6"
+    );
+
+    // Nothing is left to report
+    assert_eq!(parser.finish(), None);
+}
+
+#[test]
+fn reports_finished_games() {
+    let mut parser = LogParser::new();
+
+    let lines = [
+        r"  0:00 InitGame: \mapname\q3dm17",
+        "  1:08 Kill: 3 2 6: Isgalamido killed Mocinha by MOD_ROCKET",
+        "  1:26 Kill: 1022 4 22: <world> killed Zeh by MOD_TRIGGER_HURT",
+    ];
+
+    for line in lines {
+        assert_eq!(parser.parse_line(line).unwrap(), None);
+    }
+
+    let report = parser
+        .parse_line("  1:47 ShutdownGame:")
+        .unwrap()
+        .unwrap();
+
+    assert!(!report.incomplete);
+    assert_eq!(report.game_idx, 0);
+    assert_eq!(report.total_kills, 2);
+    assert_eq!(
+        report.settings.as_ref().unwrap().map_name,
+        "q3dm17"
+    );
+    assert_eq!(report.score_of("Isgalamido"), Some(1));
+    assert_eq!(report.score_of("Zeh"), Some(-1));
+    assert_eq!(
+        report.kills_by_means.get(CauseOfDeath::Rocket),
+        Some(1)
+    );
+
+    // The next game starts from scratch
+    parser
+        .parse_line("  0:15 Kill: 2 3 6: Zeh killed Mocinha by MOD_ROCKET")
+        .unwrap();
+
+    let report = parser
+        .parse_line("  0:20 ShutdownGame:")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(report.game_idx, 1);
+    assert_eq!(report.settings, None);
+    assert_eq!(report.total_kills, 1);
+    assert_eq!(report.score_of("Zeh"), Some(1));
+}
+
+#[test]
+fn carries_games_across_sources() {
+    let mut parser = LogParser::new();
+    let mut reports = Vec::new();
+
+    // The same log, rotated twice
+    for source in ["games.log.1", "games.log"] {
+        parser.set_source(source);
+
+        let mut reader =
+            ReallocBufReader::from("sample.log").unwrap();
+        while let Some(line) = reader.read_line().unwrap() {
+            reports.extend(parser.parse_line(line).unwrap());
+        }
+    }
+    reports.extend(parser.finish());
+
+    assert_eq!(reports.len(), 42);
+
+    for (idx, report) in reports.iter().enumerate() {
+        assert_eq!(report.game_idx as usize, idx);
+
+        let source =
+            if idx < 21 { "games.log.1" } else { "games.log" };
+        assert_eq!(report.source.as_deref(), Some(source));
+    }
+}
+
+#[test]
+fn reports_games_split_across_sources() {
+    let mut parser = LogParser::new();
+
+    let first = ReallocBufReader::new(Cursor::new(concat!(
+        "  0:00 InitGame: \\mapname\\q3dm17\n",
+        "  0:08 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET\n",
+    )))
+    .unwrap();
+    let second = ReallocBufReader::new(Cursor::new(concat!(
+        "  0:09 Kill: 3 2 7: Mal killed Zeh by MOD_ROCKET\n",
+        "  0:10 Banana: 2\n",
+    )))
+    .unwrap();
+
+    let mut reports = Vec::new();
+    let mut errors = Vec::new();
+
+    for (source, mut reader) in
+        [("games.log.1", first), ("games.log", second)]
+    {
+        parser.set_source(source);
+
+        while let Some(line) = reader.read_line().unwrap() {
+            match parser.parse_line(line) {
+                Ok(report) => reports.extend(report),
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+
+    let report = parser.finish().unwrap();
+    assert!(reports.is_empty());
+    assert_eq!(report.total_kills, 2);
+    assert_eq!(report.source.as_deref(), Some("games.log.1"));
+
+    // Line numbers start over for every source
+    let [Error::ParsingError(error)] = &errors[..] else {
+        panic!("expected a single parsing error");
+    };
+    assert_eq!(error.source.as_deref(), Some("games.log"));
+    assert_eq!(error.line, 2);
+}
+
+fn report_sample_log(order: PlayerOrder) -> Vec<u8> {
+    let mut reader =
+        ReallocBufReader::from("sample.log").unwrap();
+    let mut parser = LogParser::new().with_player_order(order);
+    let mut writer =
+        ReportWriter::new(Vec::new(), OutputFormat::Json);
+
+    while let Some(line) = reader.read_line().unwrap() {
+        if let Some(report) = parser.parse_line(line).unwrap() {
+            writer.write(&report).unwrap();
+        }
+    }
+
+    if let Some(report) = parser.finish() {
+        writer.write(&report).unwrap();
+    }
+
+    writer.finish().unwrap()
+}
+
+#[test]
+fn reports_are_reproducible() {
+    for order in [
+        PlayerOrder::FirstAppearance,
+        PlayerOrder::Score,
+        PlayerOrder::Name,
+    ] {
+        assert_eq!(
+            report_sample_log(order),
+            report_sample_log(order)
+        );
+    }
+}
+
+fn report_with_order(order: PlayerOrder) -> GameReport {
+    let mut parser = LogParser::new().with_player_order(order);
+
+    let lines = [
+        "  0:01 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET",
+        "  0:02 Kill: 4 3 7: Bob killed Mal by MOD_ROCKET",
+        "  0:03 Kill: 4 3 7: Bob killed Mal by MOD_ROCKET",
+        "  0:04 Kill: 1022 5 22: <world> killed Alice by MOD_LAVA",
+        "  0:05 Kill: 6 3 7: Carl killed Mal by MOD_ROCKET",
+    ];
+
+    for line in lines {
+        parser.parse_line(line).unwrap();
+    }
+
+    parser.parse_line("  0:06 ShutdownGame:").unwrap().unwrap()
+}
+
+#[test]
+fn orders_players() {
+    let report = report_with_order(PlayerOrder::FirstAppearance);
+    assert_eq!(
+        report.players,
+        ["Zeh", "Mal", "Bob", "Alice", "Carl"]
+    );
+
+    let report = report_with_order(PlayerOrder::Score);
+    assert_eq!(
+        report.players,
+        ["Bob", "Zeh", "Carl", "Mal", "Alice"]
+    );
+    assert_eq!(
+        report.kills,
+        [
+            ("Bob".into(), 2),
+            ("Zeh".into(), 1),
+            ("Carl".into(), 1),
+            ("Mal".into(), 0),
+            ("Alice".into(), -1)
+        ]
+    );
+
+    let report = report_with_order(PlayerOrder::Name);
+    assert_eq!(
+        report.players,
+        ["Alice", "Bob", "Carl", "Mal", "Zeh"]
+    );
+}
+
+#[test]
+fn rejects_unknown_cause_of_death() {
+    let mut parser = LogParser::new();
+
+    let result = parser.parse_line(
+        " 2:11 Kill: 2 3 7: Zeh killed Mal by MOD_BANANA",
+    );
+
+    let Err(Error::ParsingError(error)) = result else {
+        panic!("expected a parsing error");
+    };
+
+    assert_eq!(error.line, 1);
+    assert_eq!(error.column, 38);
+    assert_eq!(
+        &error.line_text[error.column - 1..],
+        "MOD_BANANA"
+    );
+}
+
+#[test]
+fn locates_errors_in_bytes_of_latin1_logs() {
+    let log = b"  0:01 Kill: 2 3 7: Z\xe9 killed Mal by MOD_ROCKET\n  0:02 Kill: 3 2 7: Mal killed Z\xe9 by MOD_BANANA\n";
+    let column = log[48..]
+        .windows(10)
+        .position(|bytes| bytes == b"MOD_BANANA")
+        .unwrap()
+        + 1;
+
+    let mut reader =
+        ReallocBufReader::new(Cursor::new(log.to_vec()))
+            .unwrap();
+    let mut parser = LogParser::new();
+
+    let line = reader.read_line().unwrap().unwrap();
+    parser.parse_line(line).unwrap();
+
+    let line = reader.read_line().unwrap().unwrap();
+    let Err(Error::ParsingError(error)) =
+        parser.parse_line(line)
+    else {
+        panic!("expected a parsing error");
+    };
+
+    // Both lines hold a single non-ASCII byte
+    assert_eq!(error.line, 2);
+    assert_eq!(error.column, column);
+    assert_eq!(error.char_column, column);
+    assert_eq!(error.offset, 48 + column - 1);
+    assert_eq!(&log[error.offset..][..10], b"MOD_BANANA");
+
+    let mut events = Events::new(Cursor::new(log.to_vec()));
+    assert!(events.next().unwrap().is_ok());
+
+    let Err(Error::ParsingError(error)) = events.next().unwrap()
+    else {
+        panic!("expected a parsing error");
+    };
+    assert_eq!(error.offset, 48 + column - 1);
+}
+
+#[test]
+fn rejects_unknown_headers() {
+    let mut parser = LogParser::new();
+
+    assert!(parser.parse_line(" 2:11 Banana: 2").is_err());
+}
+
+#[test]
+fn skips_malformed_lines_when_lenient() {
+    let mut parser =
+        LogParser::new().with_mode(ParseMode::Lenient);
+
+    let lines = [
+        " 2:11 Kill: 2 3 7: Zeh killed Mal by MOD_BANANA\n",
+        " 2:12 Banana: 2\n",
+        " 2:13 Kill: 2 3 7: Zeh killed Mal by MOD_ROCKET\n",
+    ];
+
+    for line in lines {
+        assert_eq!(parser.parse_line(line).unwrap(), None);
+    }
+
+    let report = parser
+        .parse_line(" 2:14 ShutdownGame:\n")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(report.total_kills, 1);
+
+    let skipped: Vec<_> = parser
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.line)
+        .collect();
+    assert_eq!(skipped, [1, 2]);
+}
+
+#[test]
+fn counts_causes_of_death() {
+    let mut counter: InstanceCounter<
+        CauseOfDeath,
+        CAUSES_OF_DEATH,
+    > = InstanceCounter::new();
+
+    counter.add("MOD_RAILGUN".parse().unwrap()).unwrap();
+
+    assert_eq!(counter.get(CauseOfDeath::Railgun), Some(1));
+    assert_eq!(counter.get(CauseOfDeath::Lava), Some(0));
+}